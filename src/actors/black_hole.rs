@@ -10,8 +10,9 @@ use bevy::{
 use bevy::render::RenderApp;
 use bevy::render::storage::ShaderStorageBuffer;
 use bevy::sprite::AlphaMode2d;
-use crate::actors::player::{PhysicsSystem2D, Player};
-use crate::state::MainGameState;
+use crate::actors::physics_world::{BodyHandle, PhysicsWorld};
+use crate::actors::player::{event_horizon_radius, Player};
+use crate::state::{MainGameState, PlayerTuning};
 use crate::actors::particles::CosmologicalSimulation;
 
 
@@ -44,6 +45,11 @@ impl Material2d for BlackHoleMaterial {
     }
 }
 
+// Radius of the effect mesh before scaling, it never shrinks below this
+const BLACK_HOLE_MESH_RADIUS: f32 = 60.0;
+// How far the effect mesh reaches past the accretion disk, as a multiple of the disk's radius
+const BLACK_HOLE_MESH_MARGIN: f32 = 1.25;
+
 // Component to link the black hole effect to the player
 #[derive(Component)]
 pub struct BlackHoleEffect {
@@ -83,8 +89,7 @@ fn setup_black_hole(
 
     if let Ok(player_entity) = player_result {
         // Create a mesh for the black hole effect
-        let size = 60.0; // Larger size for better lensing effect
-        let mesh = Mesh2d(meshes.add(Circle::new(size)));
+        let mesh = Mesh2d(meshes.add(Circle::new(BLACK_HOLE_MESH_RADIUS)));
 
 
         // Create the black hole material with default settings
@@ -130,26 +135,32 @@ fn update_black_hole_material(
     mut materials: ResMut<Assets<BlackHoleMaterial>>,
     time: Res<Time>,
     player_query_bh: Query<&BlackHoleEffect>,
-    player_query: Query<&PhysicsSystem2D>,
-    mut player_transform_query: Query<&mut Transform, (With<Player>, Without<BlackHoleMaterialMarker>)>,
+    player_query: Query<&BodyHandle, With<Player>>,
+    world: Res<PhysicsWorld>,
     mut black_hole_transform_query: Query<&mut Transform, (With<BlackHoleMaterialMarker>, Without<Player>)>,
     game_state: Res<MainGameState>,
+    tuning: Res<PlayerTuning>,
 ) {
     let player = player_query
         .get_single()
         .expect("Player not found");
 
-    let player_phys = &player.0
-        .get_object(0)
+    let player_phys = world
+        .body(*player)
         .expect("Player physics not found");
 
-    let mut player_transform = player_transform_query.get_single_mut()
-        .expect("There should only be one player");
     let mut bh_transform = black_hole_transform_query.get_single_mut()
         .expect("There should only be one black hole");
 
     let player_speed = player_phys.speed() as f32;
 
+    // Both radii come from the player's mass, the mesh grows to keep the accretion disk inside it
+    let horizon_radius = event_horizon_radius(player_phys.mass, &tuning);
+    let accretion_radius = horizon_radius * tuning.accretion_ratio;
+    let mesh_radius = (accretion_radius * BLACK_HOLE_MESH_MARGIN).max(BLACK_HOLE_MESH_RADIUS);
+    let mesh_scale = mesh_radius / BLACK_HOLE_MESH_RADIUS;
+    bh_transform.scale = Vec3::new(mesh_scale, mesh_scale, 1.0);
+
     for effect in player_query_bh.iter() {
         if let Some(material) = materials.get_mut(&effect.material_handle) {
             // Update time for animation
@@ -158,8 +169,9 @@ fn update_black_hole_material(
             // Adjust black hole properties based on player state
             let shield_factor = (game_state.player_shield / 100.0).clamp(0.1, 1.0);
 
-            // Adjust radius based on shield - smaller radius (more black) with higher shield
-            material.properties.radius = 0.15 - (shield_factor * 0.06);
+            // The shader's radii are fractions of the mesh's diameter in UV space
+            material.properties.radius = horizon_radius / (2.0 * mesh_radius);
+            material.properties.accretion_radius = accretion_radius / (2.0 * mesh_radius);
 
             // Stronger distortion with higher shield
             material.properties.distortion_strength = 3.0 + (shield_factor * 5.0);
@@ -179,11 +191,8 @@ fn update_black_hole_material(
                 material.properties.glow_color = Vec4::new(1.0, 0.3, 0.2, 1.0);
             }
 
-            player_transform.scale = Vec3::new(1.0 + (game_state.score as f32) * 0.001, 1.0 + (game_state.score as f32) * 0.001, 1.0);
-            bh_transform.scale = Vec3::new(1.0 + (game_state.score as f32) * 0.001, 1.0 + (game_state.score as f32) * 0.001, 1.0);
-
             // Adjust rotation speed based on player energy
-            material.properties.rotation_speed = (player_speed * 0.5) + std::f32::consts::PI;
+            material.properties.rotation_speed = (player_speed * 0.008) + std::f32::consts::PI;
         }
     }
 }
@@ -1,243 +1,493 @@
 use bevy::asset::Assets;
 use bevy::color::Color;
+use bevy::ecs::system::SystemParam;
 use bevy::math::Vec3;
 use bevy::prelude::*;
 use bevy::utils::tracing::Id;
-use rand::RngCore;
+use rand::Rng;
 use rs_physics::forces::Force;
 use rs_physics::interactions::gravitational_force;
 use rs_physics::models::{Direction2D, FromCoordinates, ObjectIn2D};
 use rs_physics::utils::{DEFAULT_PHYSICS_CONSTANTS, fast_atan2, fast_sqrt_f64, PhysicsConstants};
-use crate::actors::player::Player;
-use crate::state::MainGameState;
+use crate::actors::interpolation::PhysicsInterpolation;
+use crate::actors::physics_world::{BodyHandle, BodyKind, PhysicsWorld};
+use crate::actors::player::{event_horizon_radius, Player};
+use crate::spatial_index::{SpatialIndex, SpatialKind};
+use crate::state::{Difficulty, EnemySpawnRules, EnemyTuning, GameRng, MainGameState, PlayerTuning};
+
+pub(crate) const ENEMY_RADIUS: f32 = 3.14;
+pub(crate) const ENEMY_DRAG_COEFFICIENT: f32 = 0.47;
+pub(crate) const ENEMY_CROSS_SECTIONAL_AREA: f32 = 0.05;
+
+pub(crate) const ENEMY_PHYSICS_CONSTANTS: PhysicsConstants = PhysicsConstants {
+    gravity: 0.0,
+    ground_level: -1600.0,
+    air_density: 0.0,
+    ..DEFAULT_PHYSICS_CONSTANTS
+};
 
 #[derive(Component)]
-pub struct Enemy(rs_physics::forces::PhysicsSystem2D);
+pub struct Enemy;
+
+/// Sent when an enemy touches the player's event horizon, right before the enemy is absorbed
+#[derive(Event, Clone, Copy, Debug)]
+pub struct EnemyContact {
+    pub enemy: Entity,
+    // Where the enemy met the event horizon
+    pub point: Vec2,
+    // Points from the player's center towards the enemy
+    pub normal: Vec2,
+    // How fast the two were closing in along the normal
+    pub speed: f32,
+}
+
+/// First touch between two moving circles during a tick
+pub struct CircleContact {
+    // Fraction of the tick at which they touched, 0 if they already overlapped
+    pub time: f32,
+    pub point: Vec2,
+    pub normal: Vec2,
+}
+
+// Sweeps circle `a` from `a_from` to `a_to` against circle `b` moving from `b_from` to `b_to` over the same tick.
+// Works in `b`'s frame, so a body fast enough to pass through the other within one tick is still caught.
+// The normal points from `b` towards `a` and the point sits on `b`'s edge
+pub fn swept_circle_contact(
+    a_from: Vec2,
+    a_to: Vec2,
+    a_radius: f32,
+    b_from: Vec2,
+    b_to: Vec2,
+    b_radius: f32,
+) -> Option<CircleContact> {
+    let start = a_from - b_from;
+    let motion = (a_to - b_to) - start;
+    let reach = a_radius + b_radius;
+
+    let time = if start.length_squared() <= reach * reach {
+        0.0
+    } else {
+        // Earliest root of |start + motion * t| = reach
+        let a = motion.length_squared();
+        let b = 2.0 * start.dot(motion);
+        let c = start.length_squared() - reach * reach;
+        let discriminant = b * b - 4.0 * a * c;
+        if a <= f32::EPSILON || discriminant < 0.0 {
+            return None;
+        }
+        let time = (-b - discriminant.sqrt()) / (2.0 * a);
+        if !(0.0..=1.0).contains(&time) {
+            return None;
+        }
+        time
+    };
+
+    let a_center = a_from.lerp(a_to, time);
+    let b_center = b_from.lerp(b_to, time);
+    let normal = (a_center - b_center).try_normalize().unwrap_or(Vec2::Y);
+    Some(CircleContact {
+        time,
+        point: b_center + normal * b_radius,
+        normal,
+    })
+}
+
+// What decides when enemies appear and how fast they come in
+#[derive(SystemParam)]
+pub struct SpawnOdds<'w> {
+    rng: ResMut<'w, GameRng>,
+    difficulty: Res<'w, Difficulty>,
+    tuning: Res<'w, EnemyTuning>,
+    level_rules: Option<Res<'w, EnemySpawnRules>>,
+}
 
 pub fn spawn_enemy(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     mut game_state: ResMut<MainGameState>,
-    time: Res<Time>,
-    query: Query<&Transform, (With<Player>, Without<Enemy>)>,
+    odds: SpawnOdds,
+    mut world: ResMut<PhysicsWorld>,
+    query: Query<&BodyHandle, With<Player>>,
 ) {
-    if time.elapsed_secs_f64() < 5.0 {
+    let SpawnOdds { mut rng, difficulty, tuning, level_rules } = odds;
+
+    // The level's spawn rules win over the tuning file's
+    let tuning_rules;
+    let rules = match level_rules.as_deref() {
+        Some(rules) => rules,
+        None => {
+            tuning_rules = tuning.spawn_rules();
+            &tuning_rules
+        }
+    };
+
+    if game_state.run_time < rules.spawn_delay {
         return;
     }
-    let spawn_rate = match game_state.enemies.len() {
-        0..=10 => 0.01,
-        11..=20 => 0.03,
-        21..=30 => 0.05,
-        _ => 0.075,
-    };
-    if !rand::random_bool(spawn_rate) {
+    let spawn_rate = rules.spawn_rate(game_state.enemies.len());
+    let spawn_rate = (spawn_rate * difficulty.0 as f64).clamp(0.0, 1.0);
+    if !rng.random_bool(spawn_rate) {
         return;
     }
-    let physics_constants = PhysicsConstants {
-        gravity: 0.0,
-        ground_level: -1600.0,
-        air_density: 0.0,
-        ..DEFAULT_PHYSICS_CONSTANTS
-    };
-    let mut enemy_physics = rs_physics::forces::PhysicsSystem2D::new(physics_constants);
-
-    let player_transform = query.iter()
+    let player_handle = query.iter()
         .next()
         .expect("There should only be one player entity");
-    let spawn_x_position = rand::random_range((player_transform.translation.x - 2000.0).min(-1000.0)..=(player_transform.translation.x + 2000.0).max(1200.0));
-    let spawn_y_position = rand::random_range(player_transform.translation.y + 1000.0..=player_transform.translation.y + 1400.0);
-    let initial_velocity = rand::random_range(100.0..=200.0);
+    let Some(player_object) = world.body(*player_handle) else {
+        return;
+    };
+    let player_position = Vec2::new(player_object.position.x as f32, player_object.position.y as f32);
+    let spawn_x_position = rng.random_range((player_position.x - 2000.0).min(-1000.0)..=(player_position.x + 2000.0).max(1200.0));
+    let spawn_y_position = rng.random_range(player_position.y + 1000.0..=player_position.y + 1400.0);
+    let initial_velocity = rng.random_range(rules.min_spawn_speed..=rules.max_spawn_speed);
 
     //calculate the angle between the player and the enemy
-    let angle = fast_atan2(player_transform.translation.y - spawn_y_position as f32, player_transform.translation.x - spawn_x_position as f32);
+    let angle = fast_atan2(player_position.y - spawn_y_position as f32, player_position.x - spawn_x_position as f32);
     // calculate the x and y components of the velocity
     let x_velocity = initial_velocity * angle.cos();
     let y_velocity = initial_velocity * angle.sin();
 
     let enemy_object = ObjectIn2D::new(1.0, x_velocity as f64, y_velocity as f64, (spawn_x_position as f64, spawn_y_position as f64));
+
+    let enemy_entity = spawn_enemy_with_object(&mut commands, &mut meshes, &mut materials, &mut world, enemy_object);
+    game_state.enemies.push(enemy_entity);
+}
+
+// Helper function to spawn an enemy entity around a physics object, shared by spawning and restoring a saved run
+pub fn spawn_enemy_with_object(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    world: &mut PhysicsWorld,
+    enemy_object: ObjectIn2D,
+) -> Entity {
+    let position = Vec3::new(enemy_object.position.x as f32, enemy_object.position.y as f32, -1.0);
     let enemy_color = Color::srgb(1.0, 0.25, 0.25);
 
-    let enemy_mesh = Circle::new(3.14);
-    enemy_physics.add_object(enemy_object);
+    let enemy_mesh = Circle::new(ENEMY_RADIUS);
 
-    let enemy_entity = commands.spawn_empty().id();
-    game_state.enemies.push(enemy_entity);
-    commands.entity(enemy_entity)
-        .insert(Enemy(enemy_physics))
-        .insert(Mesh2d(
+    let enemy_entity = commands.spawn((
+        Enemy,
+        PhysicsInterpolation::new(position.truncate()),
+        Mesh2d(
             meshes.add(enemy_mesh)
-        ))
-        .insert(MeshMaterial2d(materials.add(enemy_color)))
-        .insert(Transform {
-            translation: Vec3::new(spawn_x_position, spawn_y_position as f32, -1.0),
+        ),
+        MeshMaterial2d(materials.add(enemy_color)),
+        Transform {
+            translation: position,
             ..Default::default()
-        });
+        },
+    )).id();
+
+    let handle = world.insert(enemy_entity, BodyKind::Enemy, enemy_object);
+    commands.entity(enemy_entity).insert(handle);
+    enemy_entity
 }
 
 
-pub fn update_enemy(
-    mut commands: Commands,
-    mut query: Query<(Entity, &mut Transform, &mut Enemy)>,
-    player_query: Query<&Transform, (With<Player>, Without<Enemy>)>,
-    mut game_state: ResMut<MainGameState>,
-    time: Res<Time>,
+// Pulls enemies towards the player and into orbit around it. Runs before the physics world steps,
+// the forces for every enemy go in as one batch
+pub fn apply_enemy_forces(
+    query: Query<&BodyHandle, With<Enemy>>,
+    player_query: Query<&BodyHandle, With<Player>>,
+    mut world: ResMut<PhysicsWorld>,
+    tuning: Res<EnemyTuning>,
 ) {
     // Get player position for gravitational calculations
-    let player_transform = player_query
+    let player_handle = player_query
         .iter()
         .next()
         .expect("There should only be one player entity");
+    let Some(player_object) = world.body(*player_handle) else {
+        return;
+    };
 
-    let player_x = player_transform.translation.x as f64;
-    let player_y = player_transform.translation.y as f64;
-    let player_mass = 1000000.0 * (game_state.score as f64 * 0.5).max(1.0); // Adjust this to control gravitational strength
+    let player_x = player_object.position.x;
+    let player_y = player_object.position.y;
+    // The pull follows the player's real mass, which grows with every enemy it absorbs
+    let player_mass = player_object.mass * tuning.pull_per_player_mass;
 
-    query.iter_mut()
-        .for_each(|(entity, mut transform, mut enemy)| {
-            let mut enemy: &mut Enemy = &mut enemy;
+    let mut forces = Vec::new();
+    for handle in query.iter() {
+        let Some(enemy_object) = world.body(*handle) else {
+            continue;
+        };
 
-            // Apply gravitational force toward player
-            let enemy_object = enemy.0
-                .get_object_mut(0)
-                .expect("Failed to get enemy object");
+        let dx = player_x - enemy_object.position.x;
+        let dy = player_y - enemy_object.position.y;
+        let distance_squared = dx * dx + dy * dy;
 
-            let dx = player_x - enemy_object.position.x;
-            let dy = player_y - enemy_object.position.y;
-            let distance_squared = dx * dx + dy * dy;
+        // Only apply gravity if enemy is within a certain range
+        if distance_squared < tuning.pull_range_squared {
+            // Calculate distance (with minimal value to prevent extreme forces)
+            let distance = fast_sqrt_f64(distance_squared).max(tuning.min_pull_distance);
 
-            // Only apply gravity if enemy is within a certain range
-            if distance_squared < 50000.0 { // ~707 units radius
-                // Calculate distance (with minimal value to prevent extreme forces)
-                let distance = fast_sqrt_f64(distance_squared).max(200.0);
+            let dx = dx / distance;
+            let dy = dy / distance;
 
-                let dx = dx / distance;
-                let dy = dy / distance;
+            // Calculate the maximum magnitude of the force
+            let max_force_magnitude = tuning.max_pull_force;
 
-                // Calculate the maximum magnitude of the force
-                let max_force_magnitude = 200.0; // Adjust this to control strength
+            // Calculate gravitational strength (inverse square law)
+            let gravitational_constant = distance * (1./std::f64::consts::PI); // Adjust this to control strength
+            let force_magnitude = (gravitational_constant * player_mass * enemy_object.mass / distance_squared).min(max_force_magnitude);
 
-                // Calculate gravitational strength (inverse square law)
-                let gravitational_constant = distance * (1./std::f64::consts::PI); // Adjust this to control strength
-                let force_magnitude = (gravitational_constant * player_mass * enemy_object.mass / distance_squared).min(max_force_magnitude);
+            // Calculate angle of force for the gravitational pull
+            let radial_angle = fast_atan2(dy as f32, dx as f32);
 
-                // Calculate angle of force for the gravitational pull
-                let radial_angle = fast_atan2(dy as f32, dx as f32);
+            // NEW: Add velocity dampening to help capture objects
+            // Get current velocity components
+            let vel_x = enemy_object.velocity.x;
+            let vel_y = enemy_object.velocity.y;
 
-                // NEW: Add velocity dampening to help capture objects
-                // Get current velocity components
-                let vel_x = enemy_object.velocity.x;
-                let vel_y = enemy_object.velocity.y;
+            // Calculate velocity magnitude
+            let velocity_squared = vel_x * vel_x + vel_y * vel_y;
+            let velocity_magnitude = fast_sqrt_f64(velocity_squared);
 
-                // Calculate velocity magnitude
-                let velocity_squared = vel_x * vel_x + vel_y * vel_y;
-                let velocity_magnitude = fast_sqrt_f64(velocity_squared);
+            // Apply dampening based on distance - stronger near ideal orbit
+            let ideal_orbit_distance = tuning.ideal_orbit_distance; // The distance where orbital force is strongest
+            let orbit_width = tuning.orbit_width; // How wide the "sweet spot" for orbiting is
 
-                // Apply dampening based on distance - stronger near ideal orbit
-                let ideal_orbit_distance = 400.0; // The distance where orbital force is strongest
-                let orbit_width = 200.0_f32; // How wide the "sweet spot" for orbiting is
+            // Calculate distance factor that peaks at ideal distance
+            let distance_factor = (-(distance as f32 - ideal_orbit_distance).powi(2) /
+                (2.0 * orbit_width.powi(2))).exp();
 
-                // Calculate distance factor that peaks at ideal distance
-                let distance_factor = (-(distance as f32 - ideal_orbit_distance).powi(2) /
-                    (2.0 * orbit_width.powi(2))).exp();
+            // Dampening factor - adjust as needed
+            let dampening = tuning.orbit_dampening * distance_factor as f64;
 
-                // Dampening factor - adjust as needed
-                let dampening = 0.02 * distance_factor as f64;
+            // Calculate dampening force opposing current velocity
+            let dampening_magnitude = velocity_magnitude * dampening;
 
-                // Calculate dampening force opposing current velocity
-                let dampening_magnitude = velocity_magnitude * dampening;
+            // Only apply dampening if the object has significant velocity
+            if velocity_magnitude > 10.0 {
+                let dampening_angle = fast_atan2(vel_y as f32, vel_x as f32) + std::f32::consts::PI; // Opposite to velocity
 
-                // Only apply dampening if the object has significant velocity
-                if velocity_magnitude > 10.0 {
-                    let dampening_angle = fast_atan2(vel_y as f32, vel_x as f32) + std::f32::consts::PI; // Opposite to velocity
+                let dampening_force = Force::Thrust {
+                    magnitude: dampening_magnitude,
+                    angle: dampening_angle as f64,
+                };
 
-                    let dampening_force = Force::Thrust {
-                        magnitude: dampening_magnitude,
-                        angle: dampening_angle as f64,
-                    };
+                forces.push((*handle, dampening_force));
+            }
 
-                    enemy_object.add_force(dampening_force);
-                }
+            // For clockwise orbit, subtract FRAC_PI_2 (90 degrees)
+            let orbital_angle = radial_angle - std::f32::consts::FRAC_PI_2;
+
+            // Calculate orbital coefficient - stronger at ideal orbit distance
+            let orbit_coefficient = (-(distance as f32 - ideal_orbit_distance).powi(2) /
+                (2.0 * orbit_width.powi(2))).exp();
+
+            // Adjust orbital strength based on approach angle
+            // Calculate current direction of movement relative to radial direction
+            let movement_angle = if velocity_magnitude > 0.1 {
+                fast_atan2(vel_y as f32, vel_x as f32)
+            } else {
+                0.0
+            };
+
+            // Calculate the angle between movement and radial direction
+            let angle_diff = ((movement_angle - radial_angle + std::f32::consts::PI) %
+                (2.0 * std::f32::consts::PI)) - std::f32::consts::PI;
+
+            // Calculate an approach factor (1.0 when perpendicular, lower when head-on or away)
+            let approach_factor = angle_diff.abs() / (std::f32::consts::FRAC_PI_2);
+
+            // Lower orbital force for direct approaches to prevent flinging
+            let orbital_strength_factor = tuning.orbital_strength * approach_factor as f64;
+
+            // Calculate orbital force magnitude
+            let orbital_force_magnitude = force_magnitude * orbital_strength_factor * orbit_coefficient as f64;
+
+            // Create gravitational force (inward pull)
+            let gravitational_force = Force::Thrust {
+                magnitude: force_magnitude,
+                angle: radial_angle as f64,
+            };
+
+            // Create orbital force (perpendicular to gravitational pull)
+            let orbital_force = Force::Thrust {
+                magnitude: orbital_force_magnitude,
+                angle: orbital_angle as f64,
+            };
+
+            // Apply gravitational and orbital forces
+            forces.push((*handle, gravitational_force));
+            forces.push((*handle, orbital_force));
+        }
+    }
 
-                // For clockwise orbit, subtract FRAC_PI_2 (90 degrees)
-                let orbital_angle = radial_angle - std::f32::consts::FRAC_PI_2;
+    world.apply_forces(forces);
+}
 
-                // Calculate orbital coefficient - stronger at ideal orbit distance
-                let orbit_coefficient = (-(distance as f32 - ideal_orbit_distance).powi(2) /
-                    (2.0 * orbit_width.powi(2))).exp();
+// What an enemy reaching the player changes, and by how much
+#[derive(SystemParam)]
+pub struct ContactEffects<'w> {
+    game_state: ResMut<'w, MainGameState>,
+    contacts: EventWriter<'w, EnemyContact>,
+    tuning: Res<'w, EnemyTuning>,
+    player_tuning: Res<'w, PlayerTuning>,
+}
 
-                // Adjust orbital strength based on approach angle
-                // Calculate current direction of movement relative to radial direction
-                let movement_angle = if velocity_magnitude > 0.1 {
-                    fast_atan2(vel_y as f32, vel_x as f32)
-                } else {
-                    0.0
-                };
+// Absorbs the enemies that reached the player's event horizon this tick and drops the ones that fell too low.
+// Runs after the physics world has stepped
+pub fn update_enemy(
+    mut commands: Commands,
+    query: Query<(Entity, &BodyHandle, &PhysicsInterpolation), With<Enemy>>,
+    player_query: Query<(&BodyHandle, &PhysicsInterpolation), With<Player>>,
+    mut world: ResMut<PhysicsWorld>,
+    index: Res<SpatialIndex>,
+    effects: ContactEffects,
+) {
+    let ContactEffects { mut game_state, mut contacts, tuning, player_tuning } = effects;
+    let (player_handle, player_interpolation) = player_query
+        .iter()
+        .next()
+        .expect("There should only be one player entity");
+    let Some(player_object) = world.body(*player_handle) else {
+        return;
+    };
 
-                // Calculate the angle between movement and radial direction
-                let angle_diff = ((movement_angle - radial_angle + std::f32::consts::PI) %
-                    (2.0 * std::f32::consts::PI)) - std::f32::consts::PI;
+    // Both sides of the sweep start where the bodies were at the end of the last tick
+    let player_from = player_interpolation.previous;
+    let player_to = Vec2::new(player_object.position.x as f32, player_object.position.y as f32);
+    let player_velocity = Vec2::new(player_object.velocity.x as f32, player_object.velocity.y as f32);
+    let player_radius = event_horizon_radius(player_object.mass, &player_tuning);
+    let mut absorbed_mass = 0.0;
+    let mut absorbed = Vec::new();
+
+    // Only enemies whose path this tick overlaps the player's can touch it
+    let player_reach = Vec2::splat(player_radius);
+    let nearby_enemies = index.query_aabb(player_from.min(player_to) - player_reach, player_from.max(player_to) + player_reach)
+        .into_iter()
+        .filter(|entry| entry.kind == SpatialKind::Enemy);
+    for entry in nearby_enemies {
+        let Ok((entity, handle, interpolation)) = query.get(entry.entity) else {
+            continue;
+        };
+        let Some(enemy_object) = world.body(*handle) else {
+            continue;
+        };
+        let enemy_to = Vec2::new(enemy_object.position.x as f32, enemy_object.position.y as f32);
+
+        // Check collision with player's event horizon over the whole tick
+        let contact = swept_circle_contact(interpolation.previous, enemy_to, ENEMY_RADIUS, player_from, player_to, player_radius);
+        if let Some(contact) = contact {
+            let enemy_velocity = Vec2::new(enemy_object.velocity.x as f32, enemy_object.velocity.y as f32);
+            contacts.send(EnemyContact {
+                enemy: entity,
+                point: contact.point,
+                normal: contact.normal,
+                speed: (player_velocity - enemy_velocity).dot(contact.normal),
+            });
+
+            if game_state.player_shield > 0.0 {
+                game_state.player_shield -= tuning.shield_damage;
+            } else {
+                game_state.player_hp -= tuning.hp_damage;
+            }
+            game_state.score += 1;
+            absorbed_mass += enemy_object.mass * player_tuning.absorbed_mass_gain;
+
+            // Remove the enemy upon collision
+            game_state.enemies.retain(|&id| id != entity);
+            commands.entity(entity).despawn();
+            absorbed.push(entity);
+        }
+    }
 
-                // Calculate an approach factor (1.0 when perpendicular, lower when head-on or away)
-                let approach_factor = angle_diff.abs() / (std::f32::consts::FRAC_PI_2);
+    // Remove enemies that fall too low
+    for (entity, handle, _) in query.iter() {
+        if absorbed.contains(&entity) {
+            continue;
+        }
+        let Some(enemy_object) = world.body(*handle) else {
+            continue;
+        };
+        if enemy_object.position.y as f32 <= tuning.despawn_height {
+            game_state.enemies.retain(|&id| id != entity);
+            commands.entity(entity).despawn();
+        }
+    }
 
-                // Lower orbital force for direct approaches to prevent flinging
-                let orbital_strength_factor = 0.8 * approach_factor as f64;
+    // Thrust is a force, so the heavier player also accelerates more slowly
+    if let Some(player_object) = world.body_mut(*player_handle) {
+        player_object.mass += absorbed_mass;
+    }
+}
 
-                // Calculate orbital force magnitude
-                let orbital_force_magnitude = force_magnitude * orbital_strength_factor * orbit_coefficient as f64;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-                // Create gravitational force (inward pull)
-                let gravitational_force = Force::Thrust {
-                    magnitude: force_magnitude,
-                    angle: radial_angle as f64,
-                };
+    #[test]
+    fn overlapping_circles_touch_at_the_start_of_the_tick() {
+        let contact = swept_circle_contact(
+            Vec2::new(15.0, 0.0), Vec2::new(40.0, 0.0), 10.0,
+            Vec2::ZERO, Vec2::ZERO, 10.0,
+        ).expect("overlapping circles are in contact");
 
-                // Create orbital force (perpendicular to gravitational pull)
-                let orbital_force = Force::Thrust {
-                    magnitude: orbital_force_magnitude,
-                    angle: orbital_angle as f64,
-                };
+        assert_eq!(contact.time, 0.0);
+        assert_eq!(contact.normal, Vec2::X);
+        assert_eq!(contact.point, Vec2::new(10.0, 0.0));
+    }
 
-                // Apply gravitational and orbital forces
-                enemy_object.add_force(gravitational_force);
-                enemy_object.add_force(orbital_force);
-            }
+    #[test]
+    fn circles_on_the_same_center_still_get_a_normal() {
+        let contact = swept_circle_contact(Vec2::ZERO, Vec2::ZERO, 5.0, Vec2::ZERO, Vec2::ZERO, 5.0)
+            .expect("concentric circles overlap");
 
-            // Update physics
-            enemy.0.update(time.delta_secs_f64());
+        assert_eq!(contact.time, 0.0);
+        assert_eq!(contact.normal, Vec2::Y);
+    }
 
-            let enemy_object = enemy.0.get_object(0).unwrap();
+    #[test]
+    fn fast_circle_passing_through_is_caught() {
+        // Moves 200 units in one tick, straight through a circle it never overlaps at either end
+        let contact = swept_circle_contact(
+            Vec2::new(-100.0, 0.0), Vec2::new(100.0, 0.0), 5.0,
+            Vec2::ZERO, Vec2::ZERO, 20.0,
+        ).expect("the sweep crosses the other circle");
+
+        assert!((contact.time - 0.375).abs() < 1e-5);
+        assert_eq!(contact.normal, Vec2::NEG_X);
+        assert!((contact.point - Vec2::new(-20.0, 0.0)).length() < 1e-4);
+    }
 
-            // Check collision with player
-            if (enemy_object.position.y - player_y).abs() < 30.0
-                && (enemy_object.position.x - player_x).abs() < 30.0 {
+    #[test]
+    fn both_circles_moving_meet_in_between() {
+        let contact = swept_circle_contact(
+            Vec2::new(-50.0, 0.0), Vec2::new(50.0, 0.0), 5.0,
+            Vec2::new(50.0, 0.0), Vec2::new(-50.0, 0.0), 5.0,
+        ).expect("the circles run into each other");
 
-                if game_state.player_shield > 0.0 {
-                    game_state.player_shield -= 25.0;
-                } else {
-                    game_state.player_hp -= 10.0;
-                }
-                game_state.score += 1;
+        assert!((contact.time - 0.45).abs() < 1e-5);
+        assert_eq!(contact.normal, Vec2::NEG_X);
+    }
 
-                // Remove the enemy upon collision
-                game_state.enemies.retain(|&id| id != entity);
-                commands.entity(entity).despawn();
-                return;
-            }
+    #[test]
+    fn circles_passing_each_other_by_do_not_touch() {
+        let contact = swept_circle_contact(
+            Vec2::new(-100.0, 30.0), Vec2::new(100.0, 30.0), 5.0,
+            Vec2::ZERO, Vec2::ZERO, 20.0,
+        );
+        assert!(contact.is_none());
+    }
 
-            // Remove enemies that fall too low
-            if enemy_object.position.y as f32 <= -1000.0 {
-                game_state.enemies.retain(|&id| id != entity);
-                commands.entity(entity).despawn();
-                return;
-            }
+    #[test]
+    fn circles_that_only_meet_after_the_tick_do_not_touch() {
+        let contact = swept_circle_contact(
+            Vec2::new(-100.0, 0.0), Vec2::new(-50.0, 0.0), 5.0,
+            Vec2::ZERO, Vec2::ZERO, 20.0,
+        );
+        assert!(contact.is_none());
+    }
 
-            // Update transform position
-            transform.translation = Vec3::new(
-                enemy_object.position.x as f32,
-                enemy_object.position.y as f32,
-                -1.0
-            );
-        });
-}
\ No newline at end of file
+    #[test]
+    fn resting_circles_apart_do_not_touch() {
+        let contact = swept_circle_contact(
+            Vec2::new(100.0, 0.0), Vec2::new(100.0, 0.0), 5.0,
+            Vec2::ZERO, Vec2::ZERO, 20.0,
+        );
+        assert!(contact.is_none());
+    }
+}
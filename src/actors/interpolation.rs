@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use crate::actors::physics_world::{BodyHandle, PhysicsWorld};
+
+/// Physics position of a body after the last two fixed ticks. Its Transform is drawn in between,
+/// so motion stays smooth whatever the display's refresh rate
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PhysicsInterpolation {
+    pub previous: Vec2,
+    pub current: Vec2,
+}
+
+impl PhysicsInterpolation {
+    pub fn new(position: Vec2) -> Self {
+        PhysicsInterpolation {
+            previous: position,
+            current: position,
+        }
+    }
+
+    // Jumps to a position without drawing the way there, used when a body goes through a portal
+    pub fn snap(&mut self, position: Vec2) {
+        self.previous = position;
+        self.current = position;
+    }
+}
+
+/// Pose of a wall on a path after the last two fixed ticks, drawn in between the same way as the bodies
+#[derive(Component, Clone, Copy, Debug)]
+pub struct WallInterpolation {
+    pub previous: Isometry2d,
+    pub current: Isometry2d,
+}
+
+impl WallInterpolation {
+    pub fn new(pose: Isometry2d) -> Self {
+        WallInterpolation {
+            previous: pose,
+            current: pose,
+        }
+    }
+}
+
+// Start of a fixed tick, where the bodies and walls ended up last tick becomes where they were
+pub fn begin_interpolation_tick(
+    mut query: Query<&mut PhysicsInterpolation>,
+    mut wall_query: Query<&mut WallInterpolation>,
+) {
+    for mut interpolation in query.iter_mut() {
+        interpolation.previous = interpolation.current;
+    }
+    for mut interpolation in wall_query.iter_mut() {
+        interpolation.previous = interpolation.current;
+    }
+}
+
+// End of a fixed tick, once every system has had its say about where the bodies are
+pub fn record_physics_positions(
+    mut query: Query<(&BodyHandle, &mut PhysicsInterpolation)>,
+    world: Res<PhysicsWorld>,
+) {
+    for (handle, mut interpolation) in query.iter_mut() {
+        if let Some(body) = world.body(*handle) {
+            interpolation.current = Vec2::new(body.position.x as f32, body.position.y as f32);
+        }
+    }
+}
+
+// Places each body between its last two physics positions by how far the frame is into the next tick
+pub fn interpolate_body_transforms(
+    time: Res<Time<Fixed>>,
+    mut query: Query<(&PhysicsInterpolation, &mut Transform)>,
+) {
+    let alpha = time.overstep_fraction();
+    for (interpolation, mut transform) in query.iter_mut() {
+        let position = interpolation.previous.lerp(interpolation.current, alpha);
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+// Same for the walls moving along their paths, turning the short way round between the two angles
+pub fn interpolate_wall_transforms(
+    time: Res<Time<Fixed>>,
+    mut query: Query<(&WallInterpolation, &mut Transform)>,
+) {
+    let alpha = time.overstep_fraction();
+    for (interpolation, mut transform) in query.iter_mut() {
+        let position = interpolation.previous.translation.lerp(interpolation.current.translation, alpha);
+        let rotation = interpolation.previous.rotation.slerp(interpolation.current.rotation, alpha);
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        transform.rotation = Quat::from_rotation_z(rotation.as_radians());
+    }
+}
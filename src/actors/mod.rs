@@ -4,4 +4,6 @@ pub mod enemy;
 pub mod black_hole;
 pub mod distortion;
 pub mod gravitational_lensing;
-pub mod space_time_rip;
\ No newline at end of file
+pub mod space_time_rip;
+pub mod interpolation;
+pub mod physics_world;
\ No newline at end of file
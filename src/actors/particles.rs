@@ -10,6 +10,21 @@ use rs_physics::particles::particle_interactions_barnes_hut_cosmological::{
 };
 use rs_physics::models::Velocity2D;
 use rs_physics::utils::fast_atan2;
+use serde::{Deserialize, Serialize};
+
+// Serializable copy of the simulation's SoA arrays and clock
+#[derive(Serialize, Deserialize)]
+pub struct SimulationSnapshot {
+    pub time: f64,
+    pub positions_x: Vec<f32>,
+    pub positions_y: Vec<f32>,
+    pub velocities_x: Vec<f32>,
+    pub velocities_y: Vec<f32>,
+    pub masses: Vec<f32>,
+    pub spins: Vec<f32>,
+    pub ages: Vec<f32>,
+    pub densities: Vec<f32>,
+}
 
 #[derive(Resource)]
 pub struct CosmologicalSimulation {
@@ -38,6 +53,8 @@ impl CosmologicalSimulation {
         };
 
         // Create particles in a Big Bang configuration using SoA
+        // rs_physics draws the layout from its own generator and takes no seed yet, so it differs from run to run.
+        // Quick saves store the particles themselves, and nothing in gameplay reads them
         let particle_collection = create_big_bang_particles_soa(num_particles, initial_radius as f32);
 
         Self {
@@ -182,6 +199,56 @@ impl CosmologicalSimulation {
         modify_particle_masses_soa(&mut self.particle_collection);
     }
 
+    pub fn snapshot(&self) -> SimulationSnapshot {
+        let particles = &self.particle_collection;
+        SimulationSnapshot {
+            time: self.time,
+            positions_x: particles.positions_x.clone(),
+            positions_y: particles.positions_y.clone(),
+            velocities_x: particles.velocities_x.clone(),
+            velocities_y: particles.velocities_y.clone(),
+            masses: particles.masses.clone(),
+            spins: particles.spins.clone(),
+            ages: particles.ages.clone(),
+            densities: particles.densities.clone(),
+        }
+    }
+
+    // The particle entities are spawned once per index at startup, so a snapshot can only be
+    // restored into a simulation with the same particle count
+    pub fn restore(&mut self, snapshot: SimulationSnapshot) -> Result<(), String> {
+        let count = self.particle_collection.count;
+        let lengths = [
+            snapshot.positions_x.len(),
+            snapshot.positions_y.len(),
+            snapshot.velocities_x.len(),
+            snapshot.velocities_y.len(),
+            snapshot.masses.len(),
+            snapshot.spins.len(),
+            snapshot.ages.len(),
+            snapshot.densities.len(),
+        ];
+        if lengths.iter().any(|&len| len != count) {
+            return Err(format!("expected {} particles in every array, found {:?}", count, lengths));
+        }
+
+        let particles = &mut self.particle_collection;
+        particles.positions_x = snapshot.positions_x;
+        particles.positions_y = snapshot.positions_y;
+        particles.velocities_x = snapshot.velocities_x;
+        particles.velocities_y = snapshot.velocities_y;
+        particles.masses = snapshot.masses;
+        particles.spins = snapshot.spins;
+        particles.ages = snapshot.ages;
+        particles.densities = snapshot.densities;
+        self.time = snapshot.time;
+        Ok(())
+    }
+
+    pub fn get_time(&self) -> f64 {
+        self.time
+    }
+
     pub fn get_particle_count(&self) -> usize {
         self.particle_collection.count
     }
@@ -205,6 +272,24 @@ impl CosmologicalSimulation {
     }
 }
 
+// Tunable parameters for the simulation created in `setup`
+#[derive(Resource)]
+pub struct SimulationConfig {
+    pub num_particles: usize,
+    pub theta: f64,  // Barnes-Hut approximation parameter
+    pub g: f64,      // Gravitational constant
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            num_particles: 24_000,
+            theta: 0.85,
+            g: 1.0 / std::f64::consts::PI,
+        }
+    }
+}
+
 // Particle identifier component
 #[derive(Component)]
 pub struct ParticleId(usize);
@@ -215,14 +300,15 @@ pub struct ParticleId(usize);
 pub fn setup(
     mut commands: Commands,
     time: Res<Time<Fixed>>,
+    config: Res<SimulationConfig>,
 ) {
 
     // Initialize simulation with parameters tuned for performance
-    let num_particles = 24_000;  // Adjust based on your performance requirements
+    let num_particles = config.num_particles;
     let initial_radius = 8.0 * std::f64::consts::PI.ln_1p();
     let dt = time.timestep().as_secs_f64();
-    let theta = 0.85;  // Barnes-Hut approximation parameter
-    let g = 1.0 / std::f64::consts::PI;  // Gravitational constant
+    let theta = config.theta;
+    let g = config.g;
 
     info!("Creating simulation with {} particles", num_particles);
     let start_time = std::time::Instant::now();
@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rs_physics::forces::Force;
+use rs_physics::models::ObjectIn2D;
+use crate::actors::enemy::ENEMY_PHYSICS_CONSTANTS;
+use crate::actors::player::{PHYSICS_CONSTANTS, PLAYER_CROSS_SECTIONAL_AREA, PLAYER_DRAG_COEFFICIENT};
+
+/// Points an entity at its body in the `PhysicsWorld`. A handle to a removed body goes stale
+/// instead of reaching whatever body takes over its slot
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BodyHandle {
+    index: u32,
+    generation: u32,
+}
+
+/// The physics constants and drag a body is integrated with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BodyKind {
+    Player,
+    Enemy,
+}
+
+impl BodyKind {
+    fn new_system(self) -> rs_physics::forces::PhysicsSystem2D {
+        match self {
+            BodyKind::Player => {
+                let mut system = rs_physics::forces::PhysicsSystem2D::new(PHYSICS_CONSTANTS);
+                system.apply_gravity();
+                system.apply_drag(PLAYER_DRAG_COEFFICIENT as f64, PLAYER_CROSS_SECTIONAL_AREA as f64);
+                system
+            }
+            BodyKind::Enemy => rs_physics::forces::PhysicsSystem2D::new(ENEMY_PHYSICS_CONSTANTS),
+        }
+    }
+}
+
+// Where a body lives in the shared system of its kind. Freed slots keep their object in that system,
+// so a new body of the same kind moves in without growing it
+struct BodySlot {
+    generation: u32,
+    kind: BodyKind,
+    entity: Option<Entity>,
+    object: usize,
+}
+
+/// Every dynamic body in the game, stepped together once per fixed tick.
+/// All bodies of a kind are objects of one shared `PhysicsSystem2D`
+#[derive(Resource)]
+pub struct PhysicsWorld {
+    players: rs_physics::forces::PhysicsSystem2D,
+    enemies: rs_physics::forces::PhysicsSystem2D,
+    slots: Vec<BodySlot>,
+    free: Vec<u32>,
+    entities: HashMap<Entity, BodyHandle>,
+}
+
+impl Default for PhysicsWorld {
+    fn default() -> Self {
+        PhysicsWorld {
+            players: BodyKind::Player.new_system(),
+            enemies: BodyKind::Enemy.new_system(),
+            slots: Vec::new(),
+            free: Vec::new(),
+            entities: HashMap::default(),
+        }
+    }
+}
+
+impl PhysicsWorld {
+    pub fn insert(&mut self, entity: Entity, kind: BodyKind, object: ObjectIn2D) -> BodyHandle {
+        let reusable = self.free.iter()
+            .position(|&index| self.slots[index as usize].kind == kind)
+            .map(|position| self.free.swap_remove(position));
+
+        let handle = match reusable {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.entity = Some(entity);
+                let (generation, slot_object) = (slot.generation, slot.object);
+                *self.system_mut(kind).get_object_mut(slot_object).unwrap() = object;
+                BodyHandle { index, generation }
+            }
+            None => {
+                // Slots of a kind are never dropped, so they count the objects already in its system
+                let slot_object = self.slots.iter().filter(|slot| slot.kind == kind).count();
+                self.system_mut(kind).add_object(object);
+                self.slots.push(BodySlot { generation: 0, kind, entity: Some(entity), object: slot_object });
+                BodyHandle { index: self.slots.len() as u32 - 1, generation: 0 }
+            }
+        };
+        self.entities.insert(entity, handle);
+        handle
+    }
+
+    pub fn remove(&mut self, handle: BodyHandle) {
+        let Some(slot) = self.slot_mut(handle) else {
+            return;
+        };
+        let entity = slot.entity.take();
+        slot.generation += 1;
+        if let Some(entity) = entity {
+            self.entities.remove(&entity);
+        }
+        self.free.push(handle.index);
+    }
+
+    pub fn handle_of(&self, entity: Entity) -> Option<BodyHandle> {
+        self.entities.get(&entity).copied()
+    }
+
+    pub fn body(&self, handle: BodyHandle) -> Option<&ObjectIn2D> {
+        let slot = self.slots.get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation && slot.entity.is_some())?;
+        self.system(slot.kind).get_object(slot.object)
+    }
+
+    pub fn body_mut(&mut self, handle: BodyHandle) -> Option<&mut ObjectIn2D> {
+        let (kind, slot_object) = self.slot_mut(handle).map(|slot| (slot.kind, slot.object))?;
+        self.system_mut(kind).get_object_mut(slot_object)
+    }
+
+    // Handles of every live body, for effects that reach all of them
+    pub fn handles(&self) -> impl Iterator<Item = BodyHandle> + '_ {
+        self.slots.iter().enumerate()
+            .filter(|(_, slot)| slot.entity.is_some())
+            .map(|(index, slot)| BodyHandle { index: index as u32, generation: slot.generation })
+    }
+
+    // Queues forces on many bodies at once, stale handles are skipped
+    pub fn apply_forces(&mut self, forces: impl IntoIterator<Item = (BodyHandle, Force)>) {
+        for (handle, force) in forces {
+            if let Some(body) = self.body_mut(handle) {
+                body.add_force(force);
+            }
+        }
+    }
+
+    // Integrates every body over `dt`, the player and enemy systems side by side.
+    // Freed objects are stepped along with the rest until a new body takes them over
+    pub fn step(&mut self, dt: f64) {
+        let (players, enemies) = (&mut self.players, &mut self.enemies);
+        rayon::join(|| players.update(dt), || enemies.update(dt));
+    }
+
+    fn system(&self, kind: BodyKind) -> &rs_physics::forces::PhysicsSystem2D {
+        match kind {
+            BodyKind::Player => &self.players,
+            BodyKind::Enemy => &self.enemies,
+        }
+    }
+
+    fn system_mut(&mut self, kind: BodyKind) -> &mut rs_physics::forces::PhysicsSystem2D {
+        match kind {
+            BodyKind::Player => &mut self.players,
+            BodyKind::Enemy => &mut self.enemies,
+        }
+    }
+
+    fn slot_mut(&mut self, handle: BodyHandle) -> Option<&mut BodySlot> {
+        self.slots.get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation && slot.entity.is_some())
+    }
+}
+
+// Steps the whole world on the fixed timestep
+pub fn step_physics_world(
+    mut world: ResMut<PhysicsWorld>,
+    time: Res<Time>,
+) {
+    world.step(time.delta_secs_f64());
+}
+
+// Frees the bodies of despawned entities. Runs every frame, removals are only kept around for a couple of them
+pub fn release_despawned_bodies(
+    mut removed: RemovedComponents<BodyHandle>,
+    mut world: ResMut<PhysicsWorld>,
+) {
+    for entity in removed.read() {
+        if let Some(handle) = world.handle_of(entity) {
+            world.remove(handle);
+        }
+    }
+}
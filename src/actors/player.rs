@@ -9,27 +9,32 @@ use rs_physics::forces::Force;
 use rs_physics::interactions::elastic_collision_2d;
 use rs_physics::models::{ObjectIn2D, Velocity2D};
 use rs_physics::utils::{DEFAULT_PHYSICS_CONSTANTS, fast_atan2, PhysicsConstants};
+use crate::actors::interpolation::PhysicsInterpolation;
+use crate::actors::physics_world::{BodyHandle, BodyKind, PhysicsWorld};
 use crate::hud::{EnergyBar, HpBar, ScoreCounter, ShieldBar};
-use crate::state::MainGameState;
+use crate::state::{MainGameState, PlayerInputFrame, PlayerTuning};
 
 pub(crate) const GROUND_LEVEL: f64 = -860.0;
 
-const PHYSICS_CONSTANTS: PhysicsConstants = PhysicsConstants {
+pub(crate) const PLAYER_RADIUS: f32 = 30.0;
+// Mass the player starts every run with, its event horizon is PLAYER_RADIUS at this mass
+pub(crate) const PLAYER_BASE_MASS: f64 = 65.0;
+pub(crate) const PLAYER_DRAG_COEFFICIENT: f32 = 0.47;
+pub(crate) const PLAYER_CROSS_SECTIONAL_AREA: f32 = 0.5;
+
+pub(crate) const PHYSICS_CONSTANTS: PhysicsConstants = PhysicsConstants {
     gravity: 0.0,
     ground_level: GROUND_LEVEL,
     ..DEFAULT_PHYSICS_CONSTANTS
 };
 
-#[derive(Component)]
-pub struct PhysicsSystem2D(pub rs_physics::forces::PhysicsSystem2D);
-
-impl PhysicsSystem2D {
-    fn new(constants: PhysicsConstants, player_object: ObjectIn2D) -> Self {
-        let mut physics_system = rs_physics::forces::PhysicsSystem2D::new(constants);
-        physics_system.add_object(player_object);
-        physics_system.apply_gravity();
-        physics_system.apply_drag(0.47, 0.5);
-        Self (physics_system)
+// Where the player spawns at the start of every run, set by the level
+#[derive(Resource, Clone, Copy)]
+pub struct PlayerStart(pub Vec2);
+
+impl Default for PlayerStart {
+    fn default() -> Self {
+        PlayerStart(Vec2::new(-400.0, -300.0))
     }
 }
 
@@ -50,27 +55,78 @@ pub fn setup_player(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    mut world: ResMut<PhysicsWorld>,
+    player_start: Res<PlayerStart>,
+) {
+    spawn_player(&mut commands, &mut meshes, &mut materials, &mut world, player_start.0);
+}
+
+// Helper function to spawn the player entity, shared by the initial setup and run restarts
+pub fn spawn_player(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    world: &mut PhysicsWorld,
+    start: Vec2,
 ) {
     // Player - updated to use the new ObjectIn2D::new with velocity components
-    let player_object = ObjectIn2D::new(65.0, 0.0, 0.0, (-400.0, -300.0));
+    let player_object = ObjectIn2D::new(PLAYER_BASE_MASS, 0.0, 0.0, (start.x as f64, start.y as f64));
+    spawn_player_with_object(commands, meshes, materials, world, player_object);
+}
+
+// Spawns the player around an existing physics object, used when restoring a saved run
+pub fn spawn_player_with_object(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    world: &mut PhysicsWorld,
+    player_object: ObjectIn2D,
+) {
+    let position = Vec3::new(player_object.position.x as f32, player_object.position.y as f32, 1.0);
     let player_color = Color::srgb(0.1, 0.1, 0.1);
 
-    commands
+    let player_entity = commands
         .spawn((
             Player,
             Mesh2d(
-                meshes.add(Circle::new(30.0))
+                meshes.add(Circle::new(PLAYER_RADIUS))
             ),
             MeshMaterial2d(materials.add(player_color)),
             Transform {
-                translation: Vec3::new(-400.0, -300.0, 1.0),
+                translation: position,
                 ..Default::default()
             },
-            PhysicsSystem2D::new(PHYSICS_CONSTANTS, player_object),
-        ));
+            PhysicsInterpolation::new(position.truncate()),
+        ))
+        .id();
+
+    let handle = world.insert(player_entity, BodyKind::Player, player_object);
+    commands.entity(player_entity).insert(handle);
 
 }
 
+// Radius of the player's event horizon at some mass. It grows with a power of the mass gained since the start of the run,
+// a third keeping the density constant
+pub fn event_horizon_radius(mass: f64, tuning: &PlayerTuning) -> f32 {
+    PLAYER_RADIUS * (mass / PLAYER_BASE_MASS).powf(tuning.horizon_growth_exponent) as f32
+}
+
+// Scales the player to the event horizon of its current mass. Wall and portal collisions read the radius back from the scale
+pub fn update_event_horizon(
+    mut player_query: Query<(&BodyHandle, &mut Transform), With<Player>>,
+    world: Res<PhysicsWorld>,
+    tuning: Res<PlayerTuning>,
+) {
+    for (handle, mut transform) in player_query.iter_mut() {
+        let Some(player_obj) = world.body(*handle) else {
+            continue;
+        };
+        let mass = player_obj.mass;
+        let scale = event_horizon_radius(mass, &tuning) / PLAYER_RADIUS;
+        transform.scale = Vec3::new(scale, scale, 1.0);
+    }
+}
+
 pub fn camera_movement(
     query: Query<(&Player, &Transform)>,
     mut shield_bar_query: Query<&mut Transform, (With<ShieldBar>, Without<Camera2d>, Without<Player>, Without<HpBar>, Without<EnergyBar>, Without<ScoreCounter>)>,
@@ -119,42 +175,26 @@ pub fn camera_movement(
     score_counter_transform.translation.y = camera_transform.translation.y + 500.0;
 }
 
+// Runs right after the physics world has stepped the player on the fixed timestep
 pub fn player_movement_physics (
-    mut player_query: Query<&mut PhysicsSystem2D>,
-    time: Res<Time<Fixed>>,
+    player_query: Query<&BodyHandle, With<Player>>,
+    mut world: ResMut<PhysicsWorld>,
+    tuning: Res<PlayerTuning>,
 ) {
-    player_query
-        .par_iter_mut()
-        .for_each(|mut physics_system| {
-            physics_system.0.update(1.-time.timestep().as_secs_f64());
-
-
-            let player_obj = physics_system.0.get_object_mut(0).unwrap();
-
-            // Apply velocity damping - updated to work with velocity components
-            player_obj.velocity.x *= 0.98;
-            player_obj.velocity.y *= 0.98;
-
-            // Check if velocity is very small and zero it out if so
-            if player_obj.speed() < 1.0 {
-                player_obj.velocity.x = 0.0;
-                player_obj.velocity.y = 0.0;
-            }
-
-        });
-}
+    for handle in player_query.iter() {
+        let Some(player_obj) = world.body_mut(*handle) else {
+            continue;
+        };
 
-pub fn update_player_movement(
-    mut player_transform_query: Query<&mut Transform, With<Player>>,
-    mut player_query: Query<&mut PhysicsSystem2D>,
-) {
-    let mut player_transform = player_transform_query.get_single_mut()
-        .expect("There should only be one player entity");
-    if let Ok(mut physics_system) = player_query.get_single_mut() {
-        let player_obj = physics_system.0.get_object_mut(0).unwrap();
+        // Apply velocity damping - updated to work with velocity components
+        player_obj.velocity.x *= tuning.velocity_damping;
+        player_obj.velocity.y *= tuning.velocity_damping;
 
-        player_transform.translation.x = player_obj.position.x as f32;
-        player_transform.translation.y = player_obj.position.y as f32;
+        // Check if velocity is very small and zero it out if so
+        if player_obj.speed() < tuning.stop_speed {
+            player_obj.velocity.x = 0.0;
+            player_obj.velocity.y = 0.0;
+        }
     }
 }
 
@@ -172,31 +212,32 @@ fn ground_tangent(x_pos: f32) -> (f32, f32) {
 }
 
 pub fn player_input(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut player_query: Query<&mut PhysicsSystem2D, With<Player>>,
+    input: Res<PlayerInputFrame>,
+    player_query: Query<&BodyHandle, With<Player>>,
+    mut world: ResMut<PhysicsWorld>,
     mut game_state: ResMut<MainGameState>,
-    time: Res<Time>,
+    tuning: Res<PlayerTuning>,
 ) {
-    let mut physics_system = player_query.iter_mut()
+    let handle = *player_query.iter()
         .next()
         .expect("There should only be one player entity");
-    let base_magnitude = 50.0;
+    let base_magnitude = tuning.thrust_magnitude;
 
-    if keyboard_input.just_pressed(KeyCode::Space) && game_state.player_energy >= 20.0 {
-        let player_phys_obj = physics_system.0.get_object_mut(0).unwrap();
+    if input.0.boost && game_state.player_energy >= tuning.boost_energy_cost {
+        let player_phys_obj = world.body_mut(handle).unwrap();
 
         let angle = fast_atan2(player_phys_obj.velocity.y as f32, player_phys_obj.velocity.x as f32);
 
-        let magnitude = base_magnitude * 20.0;
+        let magnitude = base_magnitude * tuning.boost_multiplier;
 
         // Apply thrust along this angle
         player_phys_obj.add_force(Force::Thrust { magnitude, angle: angle as f64 });
 
-        game_state.player_energy -= 20.0;
+        game_state.player_energy -= tuning.boost_energy_cost;
     }
 
-    if keyboard_input.pressed(KeyCode::KeyW) {
-        let player_phys_obj = physics_system.0.get_object_mut(0).unwrap();
+    if input.0.up {
+        let player_phys_obj = world.body_mut(handle).unwrap();
 
         let angle = fast_atan2(base_magnitude as f32, player_phys_obj.velocity.x as f32);
 
@@ -210,8 +251,8 @@ pub fn player_input(
         player_phys_obj.add_force(Force::Thrust { magnitude, angle: angle as f64 });
     }
 
-    if keyboard_input.pressed(KeyCode::KeyA) {
-        let player_phys_obj = physics_system.0.get_object_mut(0).unwrap();
+    if input.0.left {
+        let player_phys_obj = world.body_mut(handle).unwrap();
 
         let angle = fast_atan2(player_phys_obj.velocity.y as f32, -base_magnitude as f32);
 
@@ -225,8 +266,8 @@ pub fn player_input(
         player_phys_obj.add_force(Force::Thrust { magnitude, angle: angle as f64 });
     }
 
-    if keyboard_input.pressed(KeyCode::KeyD) {
-        let player_phys_obj = physics_system.0.get_object_mut(0).unwrap();
+    if input.0.right {
+        let player_phys_obj = world.body_mut(handle).unwrap();
 
         let angle = fast_atan2(player_phys_obj.velocity.y as f32, base_magnitude as f32);
 
@@ -239,8 +280,8 @@ pub fn player_input(
         player_phys_obj.add_force(Force::Thrust { magnitude, angle: angle as f64 });
     }
 
-    if keyboard_input.pressed(KeyCode::KeyS) {
-        let player_phys_obj = physics_system.0.get_object_mut(0).unwrap();
+    if input.0.down {
+        let player_phys_obj = world.body_mut(handle).unwrap();
 
         // Compute the angle from the tangent vector
         let angle = fast_atan2(-base_magnitude as f32, player_phys_obj.velocity.x as f32);
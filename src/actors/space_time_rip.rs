@@ -8,9 +8,15 @@ use bevy::{
 };
 use rs_physics::forces::Force;
 use rs_physics::utils::fast_atan2;
-use crate::actors::player::{PhysicsSystem2D, Player};
-use crate::props::wall_base::{Wall, WallShape};
-use crate::state::MainGameState;
+use rs_physics::models::ObjectIn2D;
+use crate::actors::enemy::{Enemy, ENEMY_RADIUS};
+use crate::actors::interpolation::PhysicsInterpolation;
+use crate::actors::physics_world::{BodyHandle, PhysicsWorld};
+use crate::actors::player::{Player, PLAYER_RADIUS};
+use crate::props::wall_base::{RipPhase, Wall};
+use crate::props::walls::WallAssets;
+use crate::spatial_index::{SpatialIndex, SpatialKind};
+use crate::state::{GameMode, GameplaySet, MainGameState, PauseState, RipEffectSnapshot, RipTuning};
 
 // Define the space-time rip shader material
 #[derive(Asset, AsBindGroup, Debug, Clone, TypePath)]
@@ -46,10 +52,39 @@ impl Material2d for SpaceTimeRipMaterial {
 #[derive(Component)]
 pub struct SpaceTimeRipEffect {
     pub material_handle: Handle<SpaceTimeRipMaterial>,
-    pub collision_width: f32, // Width of collision area
+    pub mesh_entity: Entity,  // Shader mesh drawing this rip
+    pub influence_distance: f32, // How far from the rip's line its influence reaches
     pub pull_strength: f32,   // Strength of gravitational pull
     pub energy_drain: f32,    // Energy drain per second
     pub shield_damage: f32,   // Shield damage on direct contact
+    pub phase: RipPhase,      // Where the rip is in its lifecycle
+    pub scale: f32,           // Length and pull relative to the wall, 0 while the rip is closed
+}
+
+/// Sent on the tick a rip finishes collapsing, with its line at full growth
+#[derive(Event, Clone, Copy, Debug)]
+pub struct RipCollapsed {
+    pub start: Vec2,
+    pub end: Vec2,
+}
+
+// Fragment thrown out of a collapsing rip, shrinking away over its lifetime
+#[derive(Component)]
+pub struct RipDebris {
+    pub velocity: Vec2,
+    pub remaining: f32,
+    pub lifetime: f32,
+}
+
+// Material values of a fully open rip, scaled by its lifecycle phase
+const RIP_BASE_WIDTH: f32 = 6.0;
+const RIP_BASE_GLOW: f32 = 0.8;
+const RIP_BASE_DISTORTION: f32 = 1.5;
+
+// Shader mesh spawned alongside each rip wall, linked back to the wall carrying the effect
+#[derive(Component)]
+pub struct SpaceTimeRipMesh {
+    pub wall: Entity,
 }
 
 // Plugin for the space-time rip effect
@@ -58,133 +93,339 @@ pub struct SpaceTimeRipPlugin;
 impl Plugin for SpaceTimeRipPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(Material2dPlugin::<SpaceTimeRipMaterial>::default())
-            .add_systems(Startup, setup_space_time_rips)
-            .add_systems(PostUpdate, (update_space_time_rip_material, detect_rip_collisions));
+            .add_plugins(SpaceTimeRipSimulationPlugin)
+            .add_systems(PostUpdate, (
+                follow_rip_walls.before(TransformSystem::TransformPropagate),
+                update_space_time_rip_material,
+            ));
+    }
+}
+
+// The gameplay half of the rips, usable without a renderer as long as the material asset is registered
+pub struct SpaceTimeRipSimulationPlugin;
+
+impl Plugin for SpaceTimeRipSimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RipCollapsed>()
+            .add_systems(Update, apply_rip_tuning.run_if(resource_changed::<RipTuning>))
+            .add_systems(Update, update_rip_debris.run_if(in_state(PauseState::Running)))
+            .add_systems(FixedUpdate, (
+                advance_rip_lifecycles,
+                collapse_rips,
+                traverse_rip_portals,
+                detect_rip_collisions,
+            ).chain()
+                .in_set(GameplaySet::Interactions)
+                .run_if(in_state(PauseState::Running)));
+    }
+}
+
+// Spreads the rips between -2.0 and -1.0 so none of them Z-fight
+pub fn rip_z_position(index: usize, count: usize) -> f32 {
+    -2.0 + index as f32 / count.max(1) as f32
+}
+
+// Attaches the rip effect to a wall and spawns its shader mesh, shared by the wall layout and restoring a saved run.
+// Each rip gets its own `z_position` between -2.0 and -1.0 to prevent Z-fighting with the wall and other rips
+pub fn spawn_space_time_rip(
+    commands: &mut Commands,
+    assets: &mut WallAssets,
+    wall_entity: Entity,
+    wall: &Wall,
+    z_position: f32,
+    saved: Option<&RipEffectSnapshot>,
+) {
+    // Get the corners of the wall
+    let corners = wall.get_corners();
+
+    let wall_center = (corners[0] + corners[1] + corners[2] + corners[3]) / 4.0;
+
+    // Calculate wall vectors accurately
+    let top_edge_start = corners[0]; // top_left corner
+    let top_edge_end = corners[1];   // top_right corner
+
+    // Calculate wall center and direction accurately
+    let wall_direction = (top_edge_end - top_edge_start).normalize();
+
+    // Calculate precise angle
+    let angle = wall_direction.y.atan2(wall_direction.x);
+
+    // Calculate wall length
+    let wall_length = (top_edge_end - top_edge_start).length();
+
+    // Create a material specifically tailored for this wall's orientation
+    let material_handle = assets.rip_materials.add(SpaceTimeRipMaterial {
+        properties: SpaceTimeRipProperties {
+            // Map to centered UV coordinates for consistent tearing effect
+            start_point: Vec2::new(0.0, 0.5),
+            end_point: Vec2::new(1.0, 0.5),
+            width: 8.0,
+            glow_intensity: 0.8,
+            distortion_strength: 1.5,
+            time: 0.0,
+            glow_color: Vec4::new(0.6, 0.0, 1.0, 0.8),
+            animation_speed: 0.7,
+        },
+    });
+
+    // Calculate mesh dimensions - narrower height with precise length
+    let mesh_width = wall_length;
+    let mesh_height = wall.width * 0.4; // Narrow enough to not be too rectangular
+
+    // Spawn the effect with precise positioning and rotation
+    let mesh_entity = commands.spawn((
+        SpaceTimeRipMesh { wall: wall_entity },
+        Mesh2d(assets.meshes.add(Rectangle::new(mesh_width, mesh_height))),
+        MeshMaterial2d(material_handle.clone()),
+        Transform {
+            // Position exactly at wall center
+            translation: Vec3::new(wall_center.x, wall_center.y, z_position),
+            // Apply precise rotation
+            rotation: Quat::from_rotation_z(angle),
+            scale: Vec3::ONE,
+        },
+        Visibility::default(),
+        InheritedVisibility::default(),
+        ViewVisibility::default(),
+    )).id();
+
+    // Add the effect component to the wall entity, a rip restored from a saved run keeps the values it was saved with
+    let effect = match saved {
+        Some(saved) => SpaceTimeRipEffect {
+            material_handle,
+            mesh_entity,
+            influence_distance: saved.influence_distance,
+            pull_strength: saved.pull_strength,
+            energy_drain: saved.energy_drain,
+            shield_damage: saved.shield_damage,
+            phase: saved.phase,
+            scale: saved.scale,
+        },
+        None => SpaceTimeRipEffect {
+            material_handle,
+            mesh_entity,
+            influence_distance: wall.rip_params.influence_distance.unwrap_or(assets.rip_tuning.influence_distance),
+            pull_strength: assets.rip_tuning.pull_strength * wall.rip_params.pull_scale.unwrap_or(1.0),
+            energy_drain: wall.rip_params.energy_drain.unwrap_or(assets.rip_tuning.energy_drain),
+            shield_damage: wall.rip_params.shield_damage.unwrap_or(assets.rip_tuning.shield_damage),
+            // Rips with a lifecycle stay closed until the first tick works out their phase
+            phase: if wall.rip_params.lifecycle.is_some() { RipPhase::Dormant } else { RipPhase::Open },
+            scale: if wall.rip_params.lifecycle.is_some() { 0.0 } else { 1.0 },
+        },
+    };
+    commands.entity(wall_entity).insert(effect);
+
+    info!("Space-time rip added to wall at ({}, {}) with angle {}", wall.center_x, wall.center_y, angle);
+}
+
+// Keeps each rip's shader mesh on its wall's pose. The mesh keeps its own depth and lifecycle scale
+fn follow_rip_walls(
+    mut mesh_query: Query<(&SpaceTimeRipMesh, &mut Transform)>,
+    wall_query: Query<&Transform, (With<Wall>, Without<SpaceTimeRipMesh>)>,
+) {
+    for (rip_mesh, mut mesh_transform) in mesh_query.iter_mut() {
+        let Ok(wall_transform) = wall_query.get(rip_mesh.wall) else {
+            continue;
+        };
+        mesh_transform.translation.x = wall_transform.translation.x;
+        mesh_transform.translation.y = wall_transform.translation.y;
+        mesh_transform.rotation = wall_transform.rotation;
+    }
+}
+
+// Pushes new tuning values into rips that already exist, keeping the values their level overrides
+fn apply_rip_tuning(
+    tuning: Res<RipTuning>,
+    mut rip_query: Query<(&Wall, &mut SpaceTimeRipEffect)>,
+) {
+    for (wall, mut rip_effect) in rip_query.iter_mut() {
+        rip_effect.influence_distance = wall.rip_params.influence_distance.unwrap_or(tuning.influence_distance);
+        rip_effect.pull_strength = tuning.pull_strength * wall.rip_params.pull_scale.unwrap_or(1.0);
+        rip_effect.energy_drain = wall.rip_params.energy_drain.unwrap_or(tuning.energy_drain);
+        rip_effect.shield_damage = wall.rip_params.shield_damage.unwrap_or(tuning.shield_damage);
+    }
+}
+
+// Moves rips with a lifecycle to their phase at the current run time and scales their shader mesh to match
+pub fn advance_rip_lifecycles(
+    mut rip_query: Query<(&Wall, &mut SpaceTimeRipEffect)>,
+    mut mesh_query: Query<&mut Transform, With<SpaceTimeRipMesh>>,
+    mut collapsed: EventWriter<RipCollapsed>,
+    game_state: Res<MainGameState>,
+) {
+    let time = game_state.run_time as f32;
+
+    for (wall, mut rip_effect) in rip_query.iter_mut() {
+        let Some(lifecycle) = wall.rip_params.lifecycle else {
+            continue;
+        };
+        let phase = lifecycle.phase_at(time);
+        if is_collapse(rip_effect.phase, phase) {
+            let (start, end) = scaled_segment(wall, lifecycle.max_growth);
+            collapsed.send(RipCollapsed { start, end });
+        }
+
+        rip_effect.phase = phase;
+        rip_effect.scale = lifecycle.scale(phase);
+        if let Ok(mut mesh_transform) = mesh_query.get_mut(rip_effect.mesh_entity) {
+            mesh_transform.scale.x = rip_effect.scale;
+        }
     }
 }
 
-fn setup_space_time_rips(
+// A rip that finished collapsing throws every nearby body away and breaks into debris
+fn collapse_rips(
     mut commands: Commands,
+    mut collapsed: EventReader<RipCollapsed>,
     mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<SpaceTimeRipMaterial>>,
-    wall_query: Query<(Entity, &Wall, &Transform)>,
-    window_query: Query<&Window>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut world: ResMut<PhysicsWorld>,
+    tuning: Res<RipTuning>,
 ) {
-    println!("Setting up space-time rips...");
-
-    let window = window_query.get_single().unwrap_or_else(|_| panic!("No window found"));
-    let window_width = window.width();
-    let window_height = window.height();
-
-    for (wall_entity, wall, wall_transform) in wall_query.iter() {
-        // Only create rips for walls with the SpaceTimeRip shape
-        if matches!(wall.wall_shape, WallShape::SpaceTimeRip) {
-            // Get the corners of the wall
-            let corners = wall.get_corners();
-
-            let wall_center = (corners[0] + corners[1] + corners[2] + corners[3]) / 4.0;
-
-            // Calculate wall vectors accurately
-            let top_edge_start = corners[0]; // top_left corner
-            let top_edge_end = corners[1];   // top_right corner
-
-            // Calculate wall center and direction accurately
-            let wall_direction = (top_edge_end - top_edge_start).normalize();
-
-            // Calculate precise angle
-            let angle = wall_direction.y.atan2(wall_direction.x);
-
-            // Calculate wall length
-            let wall_length = (top_edge_end - top_edge_start).length();
-
-            // Create a material specifically tailored for this wall's orientation
-            let material_handle = materials.add(SpaceTimeRipMaterial {
-                properties: SpaceTimeRipProperties {
-                    // Map to centered UV coordinates for consistent tearing effect
-                    start_point: Vec2::new(0.0, 0.5),
-                    end_point: Vec2::new(1.0, 0.5),
-                    width: 8.0,
-                    glow_intensity: 0.8,
-                    distortion_strength: 1.5,
-                    time: 0.0,
-                    glow_color: Vec4::new(0.6, 0.0, 1.0, 0.8),
-                    animation_speed: 0.7,
-                },
-            });
-
-            // Add the effect component to the wall entity
-            commands.entity(wall_entity).insert(SpaceTimeRipEffect {
-                material_handle: material_handle.clone(),
-                collision_width: wall_length,
-                pull_strength: 100.0,
-                energy_drain: 5.0,
-                shield_damage: 2.0
-            });
-
-            // Calculate mesh dimensions - narrower height with precise length
-            let mesh_width = wall_length;
-            let mesh_height = wall.width * 0.4; // Narrow enough to not be too rectangular
-
-            // Z position to prevent Z-fighting with wall
-            let z_position = rand::random_range(-2.0..-1.0);
-
-            // Spawn the effect with precise positioning and rotation
-            commands.spawn((
-                Mesh2d(meshes.add(Rectangle::new(mesh_width, mesh_height))),
-                MeshMaterial2d(material_handle),
-                Transform {
-                    // Position exactly at wall center
-                    translation: Vec3::new(wall_center.x, wall_center.y, z_position),
-                    // Apply precise rotation
-                    rotation: Quat::from_rotation_z(angle),
-                    scale: Vec3::ONE,
-                },
-                Visibility::default(),
-                InheritedVisibility::default(),
-                ViewVisibility::default(),
-            ));
+    let handles: Vec<BodyHandle> = world.handles().collect();
+    for &RipCollapsed { start, end } in collapsed.read() {
+        for &handle in &handles {
+            if let Some(body) = world.body_mut(handle) {
+                apply_shockwave(body, start, end, &tuning);
+            }
+        }
+        spawn_rip_debris(&mut commands, &mut meshes, &mut materials, start, end, &tuning);
+    }
+}
+
+// A rip collapses on the tick its phase turns to `Collapsed`, whatever it was before.
+// Without open, grow or collapse time that is straight from `Dormant`
+fn is_collapse(previous: RipPhase, phase: RipPhase) -> bool {
+    previous != RipPhase::Collapsed && phase == RipPhase::Collapsed
+}
+
+// Pushes a body straight away from the collapsed rip's line, fading out to nothing at the shockwave's radius
+fn apply_shockwave(body: &mut ObjectIn2D, start: Vec2, end: Vec2, tuning: &RipTuning) {
+    let position = Vec2::new(body.position.x as f32, body.position.y as f32);
+    let closest = closest_point_on_segment(position, start, end);
+    let distance = position.distance(closest);
+    if distance >= tuning.shockwave_radius {
+        return;
+    }
+
+    let falloff = 1.0 - distance / tuning.shockwave_radius;
+    let impulse = (position - closest).normalize_or_zero() * tuning.shockwave_speed * falloff;
+    body.velocity.x += impulse.x as f64;
+    body.velocity.y += impulse.y as f64;
+}
 
-            println!("Space-time rip added to wall at ({}, {}) with angle {}",
-                     wall.center_x, wall.center_y, angle);
+// Spreads the debris evenly along the rip, flung out to both sides at slightly different angles and speeds
+fn spawn_rip_debris(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    start: Vec2,
+    end: Vec2,
+    tuning: &RipTuning,
+) {
+    let mesh = meshes.add(Circle::new(2.5));
+    let material = materials.add(Color::srgb(0.7, 0.3, 1.0));
+    let normal = (end - start).normalize_or_zero().perp();
+
+    for i in 0..tuning.debris_count {
+        let along = (i as f32 + 0.5) / tuning.debris_count as f32;
+        let side = if i % 2 == 0 { 1.0 } else { -1.0 };
+        let spread = ((i * 7) % 5) as f32 * 0.25 - 0.5;
+        let speed = tuning.debris_speed * (0.6 + 0.4 * ((i * 3) % 4) as f32 / 3.0);
+        let position = start.lerp(end, along);
+
+        commands.spawn((
+            RipDebris {
+                velocity: Vec2::from_angle(spread).rotate(normal * side) * speed,
+                remaining: tuning.debris_lifetime,
+                lifetime: tuning.debris_lifetime,
+            },
+            Mesh2d(mesh.clone()),
+            MeshMaterial2d(material.clone()),
+            Transform::from_xyz(position.x, position.y, -0.5),
+            StateScoped(GameMode::GameRunning),
+        ));
+    }
+}
+
+fn update_rip_debris(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut debris_query: Query<(Entity, &mut Transform, &mut RipDebris)>,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut transform, mut debris) in debris_query.iter_mut() {
+        debris.remaining -= dt;
+        if debris.remaining <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
         }
+        transform.translation += (debris.velocity * dt).extend(0.0);
+        transform.scale = Vec3::splat(debris.remaining / debris.lifetime);
     }
 }
 
+// The rip's line at some scale of its wall's length, around the wall's center
+pub fn scaled_segment(wall: &Wall, scale: f32) -> (Vec2, Vec2) {
+    let (start, end) = wall.segment();
+    let center = (start + end) / 2.0;
+    (center + (start - center) * scale, center + (end - center) * scale)
+}
+
 // Collision detection system for space-time rips
-fn detect_rip_collisions(
-    mut player_query: Query<(&Transform, &mut PhysicsSystem2D), With<Player>>,
-    rip_query: Query<(&Transform, &SpaceTimeRipEffect)>,
+pub fn detect_rip_collisions(
+    player_query: Query<&BodyHandle, With<Player>>,
+    mut world: ResMut<PhysicsWorld>,
+    rip_query: Query<(&Wall, &SpaceTimeRipEffect)>,
+    index: Res<SpatialIndex>,
     mut game_state: ResMut<MainGameState>,
+    tuning: Res<RipTuning>,
     time: Res<Time>,
 ) {
     // Only process if we have a player
-    if let Ok((player_transform, mut player_physics)) = player_query.get_single_mut() {
-        let player_pos = Vec2::new(player_transform.translation.x, player_transform.translation.y);
+    if let Ok(player_handle) = player_query.get_single() {
+        // Read the position from the physics object, the Transform is only synced once per frame
+        let Some(player_obj) = world.body(*player_handle) else {
+            return;
+        };
+        let player_pos = Vec2::new(player_obj.position.x as f32, player_obj.position.y as f32);
         let dt = time.delta_secs();
 
-        // Check each space-time rip for collision
-        for (rip_transform, rip_effect) in rip_query.iter() {
-            let rip_pos = Vec2::new(rip_transform.translation.x, rip_transform.translation.y);
-            let rip_rotation = rip_transform.rotation;
+        // Only the rips whose influence zone could reach the player
+        let nearby_rips = index.query_radius(player_pos, 0.0)
+            .into_iter()
+            .filter(|entry| entry.kind == SpatialKind::Rip);
+        for entry in nearby_rips {
+            let Ok((wall, rip_effect)) = rip_query.get(entry.entity) else {
+                continue;
+            };
+            // Closed rips have no influence at all
+            if rip_effect.scale <= 0.0 {
+                continue;
+            }
+
+            // The wall is moved along its path before interactions run, so this is the rip's current line
+            let (rip_start, rip_end) = scaled_segment(wall, rip_effect.scale);
+            let rip_pos = closest_point_on_segment(player_pos, rip_start, rip_end);
 
-            // Get the distance from player to rip center
+            // Get the distance from player to the closest point on the rip
             let distance = player_pos.distance(rip_pos);
 
             // Define collision distances
-            let close_distance = rip_effect.collision_width; // Outer influence zone
-            let direct_contact = rip_effect.collision_width * 0.4; // Inner damage zone
+            let close_distance = rip_effect.influence_distance; // Outer influence zone
+            let direct_contact = rip_effect.influence_distance * tuning.direct_contact_fraction; // Inner damage zone
 
             // Apply effect if player is within influence range
             if distance < close_distance {
-                // Calculate influence factor (stronger closer to center)
+                // Calculate influence factor (stronger closer to the rip)
                 let influence = 1.0 - (distance / close_distance).clamp(0.0, 1.0);
 
-                // 1. Apply gravitational pull toward the rip center
-                let pull_direction = (rip_pos - player_pos).normalize();
-                let pull_force = rip_effect.pull_strength * influence;
+                // 1. Apply gravitational pull toward the rip's line, none once the player sits right on it
+                let pull_direction = (rip_pos - player_pos).normalize_or_zero();
+                let pull_force = rip_effect.pull_strength * rip_effect.scale * influence;
 
                 // Get physics object and apply force
-                let physics_obj = player_physics.0.get_object_mut(0).unwrap();
+                let physics_obj = world.body_mut(*player_handle).unwrap();
                 physics_obj.add_force(Force::Thrust {
                     magnitude: pull_force as f64,
                     angle: fast_atan2(pull_direction.y, pull_direction.x) as f64,
@@ -194,7 +435,7 @@ fn detect_rip_collisions(
                 let energy_drain = rip_effect.energy_drain * influence * dt;
                 game_state.player_energy = (game_state.player_energy - energy_drain).max(0.0);
 
-                // 3. Apply shield damage for direct contact with center
+                // 3. Apply shield damage for direct contact with the rip
                 if distance < direct_contact {
                     // Only damage shield if player has shield
                     if game_state.player_shield > 0.0 {
@@ -207,7 +448,7 @@ fn detect_rip_collisions(
                     }
 
                     // 4. Apply velocity distortion effect (randomize direction slightly)
-                    if physics_obj.speed() > 5.0 {
+                    if physics_obj.speed() > 300.0 {
                         // Get current velocity angle
                         let vel_angle = fast_atan2(
                             physics_obj.velocity.y as f32,
@@ -215,7 +456,7 @@ fn detect_rip_collisions(
                         );
 
                         // Add small random perturbation to angle
-                        let perturbation = (time.elapsed_secs() * 10.0).sin() * 0.2;
+                        let perturbation = (game_state.run_time as f32 * 10.0).sin() * 0.2;
                         let new_angle = vel_angle + perturbation;
 
                         // Get current speed but keep it constant
@@ -232,6 +473,137 @@ fn detect_rip_collisions(
     }
 }
 
+// Gap left between a body and the partner rip it comes out of, so it doesn't cross straight back
+const PORTAL_EXIT_MARGIN: f32 = 2.0;
+
+// Enemies, kept apart from the player so both queries can change their interpolation
+type PortalEnemies = (With<Enemy>, Without<Player>);
+
+// Sends the player and enemies that crossed a portal rip during this tick out of its partner.
+// Their velocity turns by the difference between the two rips' orientations
+pub fn traverse_rip_portals(
+    mut player_query: Query<(&Transform, &BodyHandle, &mut PhysicsInterpolation), With<Player>>,
+    mut enemy_query: Query<(&BodyHandle, &mut PhysicsInterpolation), PortalEnemies>,
+    mut world: ResMut<PhysicsWorld>,
+    rip_query: Query<(Entity, &Wall, &SpaceTimeRipEffect)>,
+    index: Res<SpatialIndex>,
+    mut game_state: ResMut<MainGameState>,
+    tuning: Res<RipTuning>,
+) {
+    let portals = paired_portals(&rip_query);
+    if portals.is_empty() {
+        return;
+    }
+
+    // Each body's path is checked from where it started the tick. Bodies that went through are snapped
+    // to their exit, so they aren't drawn sliding across the map
+    if let Ok((player_transform, player_handle, mut interpolation)) = player_query.get_single_mut() {
+        let player_obj = world.body_mut(*player_handle).unwrap();
+        let exit_offset = PLAYER_RADIUS * player_transform.scale.x + PORTAL_EXIT_MARGIN;
+        if pass_through_portals(player_obj, interpolation.previous, exit_offset, &portals, &index) {
+            interpolation.snap(Vec2::new(player_obj.position.x as f32, player_obj.position.y as f32));
+            game_state.player_energy = (game_state.player_energy - tuning.portal_energy_cost).max(0.0);
+        }
+    }
+
+    for (enemy_handle, mut interpolation) in enemy_query.iter_mut() {
+        let Some(enemy_obj) = world.body_mut(*enemy_handle) else {
+            continue;
+        };
+        if pass_through_portals(enemy_obj, interpolation.previous, ENEMY_RADIUS + PORTAL_EXIT_MARGIN, &portals, &index) {
+            interpolation.snap(Vec2::new(enemy_obj.position.x as f32, enemy_obj.position.y as f32));
+        }
+    }
+}
+
+// A portal's line at its current length and its orientation
+struct PortalSide {
+    entity: Entity,
+    start: Vec2,
+    end: Vec2,
+    rotation: f32,
+}
+
+impl PortalSide {
+    fn of(entity: Entity, wall: &Wall, rip_effect: &SpaceTimeRipEffect) -> Self {
+        let (start, end) = scaled_segment(wall, rip_effect.scale);
+        PortalSide { entity, start, end, rotation: wall.rotation_angle }
+    }
+}
+
+// Each portal rip with the rip it leads to. Rips without a partner are left out, and so are pairs where either side is closed
+fn paired_portals(rip_query: &Query<(Entity, &Wall, &SpaceTimeRipEffect)>) -> Vec<(PortalSide, PortalSide)> {
+    rip_query.iter()
+        .filter(|(_, _, rip_effect)| rip_effect.scale > 0.0)
+        .filter_map(|(entity, wall, rip_effect)| {
+            let pair = wall.portal_pair?;
+            rip_query.iter()
+                .find(|(other_entity, other, other_effect)| {
+                    other.portal_pair == Some(pair) && *other_entity != entity && other_effect.scale > 0.0
+                })
+                .map(|(partner_entity, partner, partner_effect)| {
+                    (PortalSide::of(entity, wall, rip_effect), PortalSide::of(partner_entity, partner, partner_effect))
+                })
+        })
+        .collect()
+}
+
+// Moves a body that crossed one of the portals on its way from `previous` to the same spot along its partner,
+// on the side it was heading to. Returns whether the body went through
+fn pass_through_portals(
+    body: &mut ObjectIn2D,
+    previous: Vec2,
+    exit_offset: f32,
+    portals: &[(PortalSide, PortalSide)],
+    index: &SpatialIndex,
+) -> bool {
+    let position = Vec2::new(body.position.x as f32, body.position.y as f32);
+    let velocity = Vec2::new(body.velocity.x as f32, body.velocity.y as f32);
+
+    // Only the portals the body's path went near are worth an exact crossing test
+    let path_hits = index.query_ray(previous, position - previous, previous.distance(position));
+    let crossed = portals.iter()
+        .filter(|(entry, _)| path_hits.iter().any(|(_, hit)| hit.entity == entry.entity));
+    for (entry, exit) in crossed {
+        let Some(along) = crossing_point(previous, position, entry.start, entry.end) else {
+            continue;
+        };
+
+        let entry_normal = Vec2::from_angle(entry.rotation).perp();
+        let side = entry_normal.dot(position - previous).signum();
+        let exit_normal = Vec2::from_angle(exit.rotation).perp();
+        let exit_position = exit.start.lerp(exit.end, along) + exit_normal * side * exit_offset;
+        let exit_velocity = Vec2::from_angle(exit.rotation - entry.rotation).rotate(velocity);
+
+        body.position.x = exit_position.x as f64;
+        body.position.y = exit_position.y as f64;
+        body.velocity.x = exit_velocity.x as f64;
+        body.velocity.y = exit_velocity.y as f64;
+        return true;
+    }
+    false
+}
+
+// Where the path from `from` to `to` crosses the segment, as a fraction along the segment
+fn crossing_point(from: Vec2, to: Vec2, start: Vec2, end: Vec2) -> Option<f32> {
+    let path = to - from;
+    let segment = end - start;
+    let denominator = path.perp_dot(segment);
+    if denominator.abs() < f32::EPSILON {
+        return None;
+    }
+    let path_fraction = (start - from).perp_dot(segment) / denominator;
+    let segment_fraction = (start - from).perp_dot(path) / denominator;
+    let crossed = (0.0..=1.0).contains(&path_fraction) && (0.0..=1.0).contains(&segment_fraction);
+    crossed.then_some(segment_fraction)
+}
+
+pub fn closest_point_on_segment(point: Vec2, start: Vec2, end: Vec2) -> Vec2 {
+    let segment = end - start;
+    let t = ((point - start).dot(segment) / segment.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+    start + segment * t
+}
+
 // Update the space-time rip material
 fn update_space_time_rip_material(
     mut materials: ResMut<Assets<SpaceTimeRipMaterial>>,
@@ -249,9 +621,12 @@ fn update_space_time_rip_material(
             material.properties.time = time.elapsed_secs();
 
 
-            // Make the rip width pulse slightly
+            // Make the rip width pulse slightly, on top of the lifecycle phase
             let pulse = (time.elapsed_secs().sin() * 0.2 + 1.0);
-            material.properties.width = 6.0 * pulse;
+            let (width, glow, distortion) = phase_visuals(effect.phase);
+            material.properties.width = RIP_BASE_WIDTH * pulse * width;
+            material.properties.glow_intensity = RIP_BASE_GLOW * glow;
+            material.properties.distortion_strength = RIP_BASE_DISTORTION * distortion;
 
             // Adjust the rip effect intensity based on player proximity
             if let Some(player_pos) = player_transform {
@@ -268,4 +643,113 @@ fn update_space_time_rip_material(
             }
         }
     }
-}
\ No newline at end of file
+}
+
+// Width, glow and distortion of a rip in some phase, relative to a fully open rip.
+// The tear flares bright as it opens, thickens while it grows and flashes out as it collapses
+fn phase_visuals(phase: RipPhase) -> (f32, f32, f32) {
+    match phase {
+        RipPhase::Open => (1.0, 1.0, 1.0),
+        RipPhase::Dormant | RipPhase::Collapsed => (0.0, 0.0, 0.0),
+        RipPhase::Opening(progress) => (progress, 1.0 + 1.5 * (1.0 - progress), progress),
+        RipPhase::Growing(progress) => (1.0 + 0.5 * progress, 1.0 + 0.3 * progress, 1.0 + 0.5 * progress),
+        RipPhase::Collapsing(progress) => (1.5 * (1.0 - progress), 1.3 + 2.0 * progress, 1.5 + progress),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::props::wall_base::RipLifecycle;
+
+    #[test]
+    fn closest_point_projects_onto_the_segment() {
+        let closest = closest_point_on_segment(Vec2::new(3.0, 5.0), Vec2::ZERO, Vec2::new(10.0, 0.0));
+        assert_eq!(closest, Vec2::new(3.0, 0.0));
+    }
+
+    #[test]
+    fn closest_point_clamps_to_the_ends() {
+        let (start, end) = (Vec2::ZERO, Vec2::new(10.0, 0.0));
+
+        assert_eq!(closest_point_on_segment(Vec2::new(-4.0, 2.0), start, end), start);
+        assert_eq!(closest_point_on_segment(Vec2::new(14.0, -2.0), start, end), end);
+    }
+
+    #[test]
+    fn closest_point_on_a_zero_length_segment_is_that_point() {
+        let point = Vec2::new(5.0, 5.0);
+        assert_eq!(closest_point_on_segment(Vec2::new(8.0, 1.0), point, point), point);
+    }
+
+    #[test]
+    fn crossing_point_is_the_fraction_along_the_segment() {
+        let fraction = crossing_point(Vec2::new(2.5, -1.0), Vec2::new(2.5, 1.0), Vec2::ZERO, Vec2::new(10.0, 0.0));
+        assert_eq!(fraction, Some(0.25));
+    }
+
+    #[test]
+    fn path_stopping_short_of_the_segment_does_not_cross() {
+        let fraction = crossing_point(Vec2::new(5.0, -2.0), Vec2::new(5.0, -1.0), Vec2::ZERO, Vec2::new(10.0, 0.0));
+        assert_eq!(fraction, None);
+    }
+
+    #[test]
+    fn path_past_the_end_of_the_segment_does_not_cross() {
+        let fraction = crossing_point(Vec2::new(12.0, -1.0), Vec2::new(12.0, 1.0), Vec2::ZERO, Vec2::new(10.0, 0.0));
+        assert_eq!(fraction, None);
+    }
+
+    #[test]
+    fn path_parallel_to_the_segment_does_not_cross() {
+        let (start, end) = (Vec2::ZERO, Vec2::new(10.0, 0.0));
+
+        assert_eq!(crossing_point(Vec2::new(0.0, 1.0), Vec2::new(10.0, 1.0), start, end), None);
+        assert_eq!(crossing_point(Vec2::new(-5.0, 0.0), Vec2::new(5.0, 0.0), start, end), None);
+    }
+
+    #[test]
+    fn path_ending_on_the_segment_crosses() {
+        let fraction = crossing_point(Vec2::new(5.0, 3.0), Vec2::new(5.0, 0.0), Vec2::ZERO, Vec2::new(10.0, 0.0));
+        assert_eq!(fraction, Some(0.5));
+    }
+
+    #[test]
+    fn a_path_that_does_not_move_does_not_cross() {
+        let fraction = crossing_point(Vec2::new(5.0, 0.0), Vec2::new(5.0, 0.0), Vec2::ZERO, Vec2::new(10.0, 0.0));
+        assert_eq!(fraction, None);
+    }
+
+    fn lifecycle(open_duration: f32, grow_duration: f32, collapse_duration: f32) -> RipLifecycle {
+        RipLifecycle {
+            open_at: 10.0,
+            open_duration,
+            grow_duration,
+            collapse_duration,
+            max_growth: 1.0,
+        }
+    }
+
+    #[test]
+    fn rip_collapses_once_at_the_end_of_its_lifecycle() {
+        let lifecycle = lifecycle(1.0, 1.0, 1.0);
+
+        assert!(is_collapse(lifecycle.phase_at(12.9), lifecycle.phase_at(13.0)));
+        assert!(!is_collapse(lifecycle.phase_at(13.0), lifecycle.phase_at(13.1)));
+        assert!(!is_collapse(lifecycle.phase_at(11.9), lifecycle.phase_at(12.0)));
+    }
+
+    #[test]
+    fn rip_without_open_time_collapses_straight_from_dormant() {
+        let lifecycle = lifecycle(0.0, 0.0, 0.0);
+
+        let (before, after) = (lifecycle.phase_at(9.9), lifecycle.phase_at(10.0));
+        assert_eq!((before, after), (RipPhase::Dormant, RipPhase::Collapsed));
+        assert!(is_collapse(before, after));
+    }
+
+    #[test]
+    fn rip_without_a_lifecycle_never_collapses() {
+        assert!(!is_collapse(RipPhase::Open, RipPhase::Open));
+    }
+}
@@ -0,0 +1,170 @@
+use std::path::PathBuf;
+use bevy::window::{MonitorSelection, PresentMode, WindowMode};
+use clap::{Parser, ValueEnum};
+use crate::level::{ParticleSettings, DEFAULT_LEVEL_PATH};
+use crate::level_generator::GeneratorCenter;
+use crate::render_backend::RenderBackend;
+use crate::state::ReplaySettings;
+use crate::window_plugin::WindowConfig;
+
+/// I Am Black Hole - drift through space-time rips and swallow everything that orbits you
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    /// Window width in logical pixels
+    #[arg(long, default_value_t = 1920.0, value_parser = parse_window_size)]
+    pub width: f32,
+
+    /// Window height in logical pixels
+    #[arg(long, default_value_t = 1080.0, value_parser = parse_window_size)]
+    pub height: f32,
+
+    /// Window title
+    #[arg(long, default_value = "I Am Black Hole")]
+    pub title: String,
+
+    /// How frames are presented to the display
+    #[arg(long, value_enum, default_value_t = PresentModeArg::AutoNoVsync)]
+    pub present_mode: PresentModeArg,
+
+    /// Windowed or one of the fullscreen modes
+    #[arg(long, value_enum, default_value_t = WindowModeArg::Windowed)]
+    pub window_mode: WindowModeArg,
+
+    /// Graphics API to render with, `auto` picks the platform's native one
+    #[arg(long, value_enum, env = "WGPU_BACKEND", default_value_t = RenderBackend::Auto)]
+    pub backend: RenderBackend,
+
+    /// Level file to play, relative to the assets folder
+    #[arg(long, value_name = "PATH", default_value = DEFAULT_LEVEL_PATH)]
+    pub level: String,
+
+    /// Generate the walls from the seed around the origin or the player's start, instead of the level's walls
+    #[arg(long, value_enum, value_name = "CENTER")]
+    pub generate: Option<GeneratorCenter>,
+
+    /// Number of particles in the cosmological simulation, overrides the level [default: 24000]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..=250_000))]
+    pub particles: Option<u32>,
+
+    /// Barnes-Hut opening angle, lower is more accurate and slower, overrides the level [default: 0.85]
+    #[arg(long, value_parser = parse_theta)]
+    pub theta: Option<f64>,
+
+    /// Gravitational constant of the cosmological simulation, overrides the level [default: 1/π]
+    #[arg(long, value_parser = parse_positive_f64)]
+    pub g: Option<f64>,
+
+    /// Seed for all gameplay randomness, random when omitted
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Starting difficulty, scales how often enemies spawn
+    #[arg(long, default_value_t = 1.0, value_parser = parse_difficulty)]
+    pub difficulty: f32,
+
+    /// Run without a window or renderer for the given number of fixed ticks, then print a summary
+    #[arg(long, value_name = "TICKS")]
+    pub headless: Option<u32>,
+
+    /// Record player input to a replay file
+    #[arg(long, value_name = "PATH", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Play back a replay file, its seed overrides --seed
+    #[arg(long, value_name = "PATH")]
+    pub replay: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum PresentModeArg {
+    AutoVsync,
+    AutoNoVsync,
+    Fifo,
+    FifoRelaxed,
+    Immediate,
+    Mailbox,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum WindowModeArg {
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl Cli {
+    // Particle settings given on the command line, these win over the level's
+    pub fn particle_overrides(&self) -> ParticleSettings {
+        ParticleSettings {
+            num_particles: self.particles.map(|particles| particles as usize),
+            theta: self.theta,
+            g: self.g,
+        }
+    }
+
+    // Everything a replay was recorded with wins over the command line
+    pub fn use_replay_settings(&mut self, settings: &ReplaySettings) {
+        self.seed = Some(settings.seed);
+        self.difficulty = settings.difficulty;
+        self.level = settings.level.clone();
+        self.generate = settings.generate;
+    }
+
+    pub fn window_config(&self) -> WindowConfig {
+        let present_mode = match self.present_mode {
+            PresentModeArg::AutoVsync => PresentMode::AutoVsync,
+            PresentModeArg::AutoNoVsync => PresentMode::AutoNoVsync,
+            PresentModeArg::Fifo => PresentMode::Fifo,
+            PresentModeArg::FifoRelaxed => PresentMode::FifoRelaxed,
+            PresentModeArg::Immediate => PresentMode::Immediate,
+            PresentModeArg::Mailbox => PresentMode::Mailbox,
+        };
+        let mode = match self.window_mode {
+            WindowModeArg::Windowed => WindowMode::Windowed,
+            WindowModeArg::Borderless => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+            WindowModeArg::Fullscreen => WindowMode::Fullscreen(MonitorSelection::Current),
+        };
+
+        WindowConfig {
+            width: self.width,
+            height: self.height,
+            title: self.title.clone(),
+            mode,
+            present_mode,
+            ..WindowConfig::default()
+        }
+    }
+}
+
+fn parse_window_size(value: &str) -> Result<f32, String> {
+    let size: f32 = value.parse().map_err(|_| format!("'{}' is not a number", value))?;
+    if !(320.0..=16384.0).contains(&size) {
+        return Err(format!("{} is outside the supported range 320..=16384", size));
+    }
+    Ok(size)
+}
+
+fn parse_theta(value: &str) -> Result<f64, String> {
+    let theta: f64 = value.parse().map_err(|_| format!("'{}' is not a number", value))?;
+    if !(theta > 0.0 && theta <= 2.0) {
+        return Err(format!("{} is outside the supported range (0, 2]", theta));
+    }
+    Ok(theta)
+}
+
+fn parse_positive_f64(value: &str) -> Result<f64, String> {
+    let number: f64 = value.parse().map_err(|_| format!("'{}' is not a number", value))?;
+    if !(number.is_finite() && number > 0.0) {
+        return Err(format!("{} must be a positive number", number));
+    }
+    Ok(number)
+}
+
+fn parse_difficulty(value: &str) -> Result<f32, String> {
+    let difficulty: f32 = value.parse().map_err(|_| format!("'{}' is not a number", value))?;
+    if !(0.1..=10.0).contains(&difficulty) {
+        return Err(format!("{} is outside the supported range 0.1..=10", difficulty));
+    }
+    Ok(difficulty)
+}
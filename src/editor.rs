@@ -0,0 +1,505 @@
+use bevy::ecs::system::SystemParam;
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::window::PrimaryWindow;
+use crate::actors::interpolation::WallInterpolation;
+use crate::actors::player::PlayerStart;
+use crate::actors::space_time_rip::{rip_z_position, spawn_space_time_rip, SpaceTimeRipEffect};
+use crate::level::{write_level, Level, LevelHandle, LevelPath, WallSpec};
+use crate::props::wall_base::{RipParams, Wall, WallShape};
+use crate::props::walls::{spawn_wall_entity, wall_color, wall_mesh, wall_outline, WallAssets, WallOrder};
+use crate::state::{EnemySpawnRules, GameMode};
+
+// How far from a wall's edge a click still picks it, in world units
+const PICK_TOLERANCE: f32 = 8.0;
+const ROTATION_STEP_DEGREES: f32 = 7.5;
+const SIZE_STEP: f32 = 1.1;
+const RIP_VALUE_STEP: f32 = 1.1;
+const NEW_WALL_SIZE: Vec2 = Vec2::new(200.0, 16.0);
+const NEW_WALL_CURVATURE: f32 = 0.005;
+const SELECTION_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+const RIP_OUTLINE_COLOR: Color = Color::srgba(0.6, 0.0, 1.0, 0.6);
+
+const EDITOR_HELP: &str = "Level editor - F2 to leave\n\
+    Click to select, drag to move, N to place a wall, Delete to remove it\n\
+    Wheel rotates, Shift+wheel resizes the length, Ctrl+wheel the thickness\n\
+    Tab cycles the shape, 1-4 pick a rip value and +/- change it\n\
+    Ctrl+S saves the level";
+
+// Which `SpaceTimeRipEffect` value +/- changes
+#[derive(Default, Clone, Copy, Debug)]
+enum RipField {
+    #[default]
+    InfluenceDistance,
+    PullScale,
+    EnergyDrain,
+    ShieldDamage,
+}
+
+#[derive(Resource, Default)]
+struct EditorSelection {
+    wall: Option<Entity>,
+    // Order of the selected wall, which finds it again once a reload has respawned the walls
+    order: Option<WallOrder>,
+    // Offset from the cursor to the wall's center while dragging
+    drag_offset: Option<Vec2>,
+    rip_field: RipField,
+}
+
+// Sent whenever a wall's `Wall` component was edited, so its mesh, material and rip can be rebuilt
+#[derive(Event)]
+struct WallEdited(Entity);
+
+#[derive(Component)]
+struct EditorStatus;
+
+/// Mouse driven editing of the level's walls and rips, entered from the menu with F2
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorSelection>()
+            .add_event::<WallEdited>()
+            .add_systems(Update, toggle_editor.run_if(in_state(GameMode::Menu).or(in_state(GameMode::Editor))))
+            .add_systems(OnEnter(GameMode::Editor), spawn_editor_status)
+            .add_systems(OnExit(GameMode::Editor), clear_selection)
+            .add_systems(Update, (
+                select_and_drag_walls,
+                place_wall,
+                delete_selected_wall,
+                transform_selected_wall,
+                cycle_selected_shape,
+                edit_selected_rip,
+                rebuild_edited_walls,
+                save_level,
+                update_editor_status,
+                follow_reloaded_selection,
+            ).chain().run_if(in_state(GameMode::Editor)))
+            .add_systems(PostUpdate, draw_editor_outlines
+                .after(TransformSystem::TransformPropagate)
+                .run_if(in_state(GameMode::Editor)));
+    }
+}
+
+fn toggle_editor(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mode: Res<State<GameMode>>,
+    mut next_mode: ResMut<NextState<GameMode>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F2) {
+        return;
+    }
+    match mode.get() {
+        GameMode::Menu => next_mode.set(GameMode::Editor),
+        GameMode::Editor => next_mode.set(GameMode::Menu),
+        _ => {}
+    }
+}
+
+fn spawn_editor_status(
+    mut commands: Commands,
+) {
+    commands.spawn((
+        EditorStatus,
+        Text::new(EDITOR_HELP),
+        TextFont {
+            font_size: 16.0,
+            ..Default::default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(12.0),
+            bottom: Val::Px(12.0),
+            ..Default::default()
+        },
+        StateScoped(GameMode::Editor),
+    ));
+}
+
+fn clear_selection(
+    mut selection: ResMut<EditorSelection>,
+) {
+    *selection = EditorSelection::default();
+}
+
+#[derive(SystemParam)]
+struct Cursor<'w, 's> {
+    window_query: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    camera_query: Query<'w, 's, (&'static Camera, &'static GlobalTransform)>,
+}
+
+impl Cursor<'_, '_> {
+    // World position under the cursor, seen through the camera drawing to the window
+    fn world_position(&self) -> Option<Vec2> {
+        let cursor = self.window_query.get_single().ok()?.cursor_position()?;
+        self.camera_query.iter()
+            .find(|(camera, _)| matches!(camera.target, RenderTarget::Window(_)))
+            .and_then(|(camera, camera_transform)| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+    }
+}
+
+// Saving reloads the level, which respawns every wall. The wall in the selected one's place takes over the selection
+fn follow_reloaded_selection(
+    wall_query: Query<(Entity, &WallOrder)>,
+    mut selection: ResMut<EditorSelection>,
+) {
+    let Some(entity) = selection.wall else {
+        return;
+    };
+    if let Ok((_, order)) = wall_query.get(entity) {
+        selection.order = Some(*order);
+        return;
+    }
+
+    let order = selection.order;
+    selection.wall = wall_query.iter()
+        .find(|(_, wall_order)| Some(**wall_order) == order)
+        .map(|(entity, _)| entity);
+    selection.drag_offset = None;
+}
+
+fn select_and_drag_walls(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    cursor: Cursor,
+    mut wall_query: Query<(Entity, &mut Wall)>,
+    mut selection: ResMut<EditorSelection>,
+    mut edited: EventWriter<WallEdited>,
+) {
+    if mouse_input.just_released(MouseButton::Left) {
+        selection.drag_offset = None;
+    }
+    let Some(cursor) = cursor.world_position() else {
+        return;
+    };
+
+    if mouse_input.just_pressed(MouseButton::Left) {
+        // Pick the wall whose edge is closest to the cursor
+        let picked = wall_query.iter()
+            .map(|(entity, wall)| (entity, wall.closest_point(cursor).distance(cursor), wall))
+            .filter(|(_, distance, _)| *distance <= PICK_TOLERANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(entity, _, wall)| (entity, Vec2::new(wall.center_x, wall.center_y) - cursor));
+
+        selection.wall = picked.map(|(entity, _)| entity);
+        selection.drag_offset = picked.map(|(_, offset)| offset);
+        return;
+    }
+
+    let (Some(entity), Some(offset)) = (selection.wall, selection.drag_offset) else {
+        return;
+    };
+    if !mouse_input.pressed(MouseButton::Left) {
+        return;
+    }
+    let Ok((_, mut wall)) = wall_query.get_mut(entity) else {
+        selection.wall = None;
+        return;
+    };
+
+    let delta = cursor + offset - Vec2::new(wall.center_x, wall.center_y);
+    if delta != Vec2::ZERO {
+        wall.translate(delta);
+        edited.send(WallEdited(entity));
+    }
+}
+
+// New walls go to the end of the level's list
+fn place_wall(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    cursor: Cursor,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    order_query: Query<&WallOrder>,
+    mut selection: ResMut<EditorSelection>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyN) {
+        return;
+    }
+    let Some(cursor) = cursor.world_position() else {
+        return;
+    };
+
+    let wall = Wall::new_rigid(cursor.x, cursor.y, NEW_WALL_SIZE.x, NEW_WALL_SIZE.y, 0.0);
+    let order = WallOrder(order_query.iter().map(|order| order.0 + 1).max().unwrap_or(0));
+    selection.wall = Some(spawn_wall_entity(&mut commands, &mut meshes, &mut materials, wall, order));
+    selection.drag_offset = None;
+}
+
+fn delete_selected_wall(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    rip_query: Query<&SpaceTimeRipEffect>,
+    mut wall_query: Query<(Entity, &mut Wall)>,
+    mut selection: ResMut<EditorSelection>,
+) {
+    if !keyboard_input.any_just_pressed([KeyCode::Delete, KeyCode::Backspace]) {
+        return;
+    }
+    let Some(entity) = selection.wall.take() else {
+        return;
+    };
+
+    if let Ok(rip_effect) = rip_query.get(entity) {
+        commands.entity(rip_effect.mesh_entity).despawn();
+    }
+    if let Some(pair) = wall_query.get(entity).ok().and_then(|(_, wall)| wall.portal_pair) {
+        unlink_portal_partner(&mut wall_query, entity, pair);
+    }
+    commands.entity(entity).despawn();
+    selection.drag_offset = None;
+}
+
+// A portal needs both of its rips, so once one of them is gone the other one is unlinked too
+fn unlink_portal_partner(wall_query: &mut Query<(Entity, &mut Wall)>, entity: Entity, pair: u32) {
+    for (other, mut wall) in wall_query.iter_mut() {
+        if other != entity && wall.portal_pair == Some(pair) {
+            wall.portal_pair = None;
+        }
+    }
+}
+
+// Wheel rotates the selected wall, with Shift it scales the length and with Ctrl the thickness
+fn transform_selected_wall(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut wheel_events: EventReader<MouseWheel>,
+    mut wall_query: Query<&mut Wall>,
+    selection: Res<EditorSelection>,
+    mut edited: EventWriter<WallEdited>,
+) {
+    let scroll: f32 = wheel_events.read().map(|event| event.y.signum()).sum();
+    if scroll == 0.0 {
+        return;
+    }
+    let Some(entity) = selection.wall else {
+        return;
+    };
+    let Ok(mut wall) = wall_query.get_mut(entity) else {
+        return;
+    };
+
+    if keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        wall.width *= SIZE_STEP.powf(scroll);
+    } else if keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        wall.height *= SIZE_STEP.powf(scroll);
+    } else {
+        wall.rotate((ROTATION_STEP_DEGREES * scroll).to_radians());
+    }
+    edited.send(WallEdited(entity));
+}
+
+// Rigid -> Convex -> Concave -> SpaceTimeRip -> Rigid
+fn cycle_selected_shape(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut wall_query: Query<(Entity, &mut Wall)>,
+    selection: Res<EditorSelection>,
+    mut edited: EventWriter<WallEdited>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Tab) {
+        return;
+    }
+    let Some(entity) = selection.wall else {
+        return;
+    };
+    let Ok((_, mut wall)) = wall_query.get_mut(entity) else {
+        return;
+    };
+
+    wall.wall_shape = match wall.wall_shape {
+        WallShape::Rigid => WallShape::Convex(NEW_WALL_CURVATURE),
+        WallShape::Convex(curvature) => WallShape::Concave(curvature),
+        WallShape::Concave(_) => WallShape::SpaceTimeRip,
+        WallShape::SpaceTimeRip => WallShape::Rigid,
+    };
+
+    // Rip values and portals are only valid on rips, the level wouldn't save with them left on another shape
+    if !matches!(wall.wall_shape, WallShape::SpaceTimeRip) {
+        wall.rip_params = RipParams::default();
+        if let Some(pair) = wall.portal_pair.take() {
+            unlink_portal_partner(&mut wall_query, entity, pair);
+        }
+    }
+    edited.send(WallEdited(entity));
+}
+
+// 1-4 pick the value, +/- scale it. The value is stored on the wall so it is saved with the level
+fn edit_selected_rip(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut wall_query: Query<(&mut Wall, &SpaceTimeRipEffect)>,
+    mut selection: ResMut<EditorSelection>,
+    mut edited: EventWriter<WallEdited>,
+) {
+    let picked_field = [
+        (KeyCode::Digit1, RipField::InfluenceDistance),
+        (KeyCode::Digit2, RipField::PullScale),
+        (KeyCode::Digit3, RipField::EnergyDrain),
+        (KeyCode::Digit4, RipField::ShieldDamage),
+    ].into_iter().find(|(key, _)| keyboard_input.just_pressed(*key));
+    if let Some((_, field)) = picked_field {
+        selection.rip_field = field;
+    }
+
+    let factor = if keyboard_input.any_just_pressed([KeyCode::Equal, KeyCode::NumpadAdd]) {
+        RIP_VALUE_STEP
+    } else if keyboard_input.any_just_pressed([KeyCode::Minus, KeyCode::NumpadSubtract]) {
+        1.0 / RIP_VALUE_STEP
+    } else {
+        return;
+    };
+    let Some(entity) = selection.wall else {
+        return;
+    };
+    let Ok((mut wall, rip_effect)) = wall_query.get_mut(entity) else {
+        return;
+    };
+
+    // Start from the effect's current value, it already has the tuning defaults applied. The pull is kept as a multiple of the tuning's
+    let rip_params = &mut wall.rip_params;
+    match selection.rip_field {
+        RipField::InfluenceDistance => rip_params.influence_distance = Some(rip_effect.influence_distance * factor),
+        RipField::PullScale => rip_params.pull_scale = Some(rip_params.pull_scale.unwrap_or(1.0) * factor),
+        RipField::EnergyDrain => rip_params.energy_drain = Some(rip_effect.energy_drain * factor),
+        RipField::ShieldDamage => rip_params.shield_damage = Some(rip_effect.shield_damage * factor),
+    }
+    edited.send(WallEdited(entity));
+}
+
+// Everything on a wall entity that follows its `Wall`
+type WallVisuals = (
+    &'static Wall,
+    &'static mut Transform,
+    &'static Mesh2d,
+    &'static MeshMaterial2d<ColorMaterial>,
+    Option<&'static SpaceTimeRipEffect>,
+    Option<&'static mut WallInterpolation>,
+);
+
+// Brings the transform, mesh, material and rip of edited walls back in line with their `Wall`
+fn rebuild_edited_walls(
+    mut commands: Commands,
+    mut events: EventReader<WallEdited>,
+    mut assets: WallAssets,
+    mut wall_query: Query<WallVisuals>,
+    rip_count_query: Query<(), With<SpaceTimeRipEffect>>,
+) {
+    let mut rebuilt = Vec::new();
+    for WallEdited(entity) in events.read() {
+        if rebuilt.contains(entity) {
+            continue;
+        }
+        rebuilt.push(*entity);
+        let Ok((wall, mut transform, mesh, material, rip_effect, interpolation)) = wall_query.get_mut(*entity) else {
+            continue;
+        };
+
+        transform.translation.x = wall.center_x;
+        transform.translation.y = wall.center_y;
+        transform.rotation = Quat::from_rotation_z(wall.rotation_angle);
+        if let Some(mut interpolation) = interpolation {
+            *interpolation = WallInterpolation::new(wall.pose());
+        }
+        assets.meshes.insert(&mesh.0, wall_mesh(wall));
+        if let Some(material) = assets.materials.get_mut(&material.0) {
+            material.color = wall_color(&wall.wall_shape);
+        }
+
+        // The rip's mesh and material follow the wall's size and angle, so they are simply made again
+        if let Some(rip_effect) = rip_effect {
+            commands.entity(rip_effect.mesh_entity).despawn();
+            commands.entity(*entity).remove::<SpaceTimeRipEffect>();
+        }
+        if !wall.is_solid() {
+            let rip_count = rip_count_query.iter().count();
+            let z_position = rip_z_position(rip_count, rip_count + 1);
+            spawn_space_time_rip(&mut commands, &mut assets, *entity, wall, z_position, None);
+        }
+    }
+}
+
+fn save_level(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    wall_query: Query<(&Wall, &WallOrder)>,
+    levels: Res<Assets<Level>>,
+    handle: Res<LevelHandle>,
+    path: Res<LevelPath>,
+    player_start: Res<PlayerStart>,
+    spawn_rules: Option<Res<EnemySpawnRules>>,
+) {
+    let ctrl = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if !(ctrl && keyboard_input.just_pressed(KeyCode::KeyS)) {
+        return;
+    }
+    let Some(loaded_level) = levels.get(&handle.0) else {
+        warn!("Level {} hasn't finished loading, nothing saved", path.0);
+        return;
+    };
+
+    // Keep the walls in level order, so the rips keep their depth order after reloading
+    let mut walls: Vec<(&Wall, &WallOrder)> = wall_query.iter().collect();
+    walls.sort_by_key(|(_, order)| **order);
+
+    let level = Level {
+        name: loaded_level.name.clone(),
+        player_start: (player_start.0.x, player_start.0.y),
+        enemy_spawns: spawn_rules.map(|rules| rules.clone()),
+        particles: loaded_level.particles.clone(),
+        walls: walls.into_iter().map(|(wall, _)| WallSpec::from_wall(wall)).collect(),
+    };
+
+    match write_level(&path.0, &level) {
+        Ok(()) => info!("Saved {} walls to {}", level.walls.len(), path.0),
+        Err(e) => error!("Failed to save level {}: {}", path.0, e),
+    }
+}
+
+fn update_editor_status(
+    selection: Res<EditorSelection>,
+    wall_query: Query<(&Wall, Option<&SpaceTimeRipEffect>)>,
+    mut status_query: Query<&mut Text, With<EditorStatus>>,
+) {
+    let Ok(mut status) = status_query.get_single_mut() else {
+        return;
+    };
+
+    let selected = selection.wall.and_then(|entity| wall_query.get(entity).ok());
+    status.0 = match selected {
+        None => EDITOR_HELP.to_string(),
+        Some((wall, rip_effect)) => {
+            let mut text = format!(
+                "{}\n\n{:?} at ({:.0}, {:.0}), {:.0} x {:.0}, {:.1} deg",
+                EDITOR_HELP, wall.wall_shape, wall.center_x, wall.center_y,
+                wall.width, wall.height, wall.rotation_angle.to_degrees()
+            );
+            if let Some(rip_effect) = rip_effect {
+                text += &format!(
+                    "\nEditing {:?} - reach {:.1}, pull {:.1}, drain {:.1}, shield damage {:.1}",
+                    selection.rip_field, rip_effect.influence_distance, rip_effect.pull_strength,
+                    rip_effect.energy_drain, rip_effect.shield_damage
+                );
+            }
+            text
+        }
+    };
+}
+
+// Rips have no outline outside the editor, here they need one to be picked
+fn draw_editor_outlines(
+    mut gizmos: Gizmos,
+    wall_query: Query<(Entity, &Wall, &GlobalTransform)>,
+    selection: Res<EditorSelection>,
+) {
+    for (entity, wall, transform) in wall_query.iter() {
+        let color = if selection.wall == Some(entity) {
+            SELECTION_COLOR
+        } else if !wall.is_solid() {
+            RIP_OUTLINE_COLOR
+        } else {
+            continue;
+        };
+        let outline = wall_outline(wall)
+            .into_iter()
+            .map(|point| transform.transform_point(point.extend(0.0)).truncate());
+        gizmos.linestrip_2d(outline, color);
+    }
+}
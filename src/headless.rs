@@ -0,0 +1,116 @@
+use std::time::Duration;
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::asset::AssetPlugin;
+use bevy::input::InputPlugin;
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use crate::actors::enemy::Enemy;
+use crate::actors::particles::CosmologicalSimulation;
+use crate::actors::physics_world::{BodyHandle, PhysicsWorld};
+use crate::actors::player::Player;
+use crate::actors::space_time_rip::{SpaceTimeRipMaterial, SpaceTimeRipSimulationPlugin};
+use crate::state::{run_assets_ready, GameMode, GameplaySet, MainGameState};
+
+/// Runs the gameplay and physics systems on `MinimalPlugins` for a fixed number of ticks.
+/// Nothing here touches a window or the GPU, so it works on CI machines
+pub struct HeadlessPlugin {
+    pub ticks: u32,
+}
+
+#[derive(Resource)]
+struct HeadlessRun {
+    ticks: u32,
+    ticks_run: u32,
+    started: std::time::Instant,
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        // Every update advances time by exactly one fixed step, so each update runs one `FixedUpdate` tick
+        let timestep = Time::<Fixed>::default().timestep();
+
+        app.add_plugins((
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::ZERO)),
+            LogPlugin::default(),
+            StatesPlugin,
+            InputPlugin,
+            AssetPlugin::default(),
+        ))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(timestep))
+            .init_asset::<Mesh>()
+            .init_asset::<ColorMaterial>()
+            .init_asset::<SpaceTimeRipMaterial>()
+            .insert_resource(HeadlessRun {
+                ticks: self.ticks,
+                ticks_run: 0,
+                started: std::time::Instant::now(),
+            })
+            .add_plugins(SpaceTimeRipSimulationPlugin)
+            .add_systems(Update, start_headless_run
+                .run_if(in_state(GameMode::Menu))
+                .run_if(run_assets_ready))
+            .add_systems(FixedUpdate, count_headless_ticks
+                .after(GameplaySet::Interactions)
+                .run_if(in_state(GameMode::GameRunning)))
+            .add_systems(OnEnter(GameMode::GameOver), finish_headless_run);
+    }
+}
+
+// There is nobody to press Enter, so the run starts as soon as the level and tuning are in place
+fn start_headless_run(mut next_mode: ResMut<NextState<GameMode>>) {
+    next_mode.set(GameMode::GameRunning);
+}
+
+fn count_headless_ticks(
+    mut run: ResMut<HeadlessRun>,
+    game_state: Res<MainGameState>,
+    player_query: Query<&BodyHandle, With<Player>>,
+    enemy_query: Query<&Enemy>,
+    world: Res<PhysicsWorld>,
+    simulation: Res<CosmologicalSimulation>,
+    mut exit: EventWriter<AppExit>,
+) {
+    run.ticks_run += 1;
+    if run.ticks_run >= run.ticks {
+        print_summary(&run, &game_state, &player_query, &enemy_query, &world, &simulation, "tick limit reached");
+        exit.send(AppExit::Success);
+    }
+}
+
+fn finish_headless_run(
+    run: Res<HeadlessRun>,
+    game_state: Res<MainGameState>,
+    player_query: Query<&BodyHandle, With<Player>>,
+    enemy_query: Query<&Enemy>,
+    world: Res<PhysicsWorld>,
+    simulation: Res<CosmologicalSimulation>,
+    mut exit: EventWriter<AppExit>,
+) {
+    print_summary(&run, &game_state, &player_query, &enemy_query, &world, &simulation, "player died");
+    exit.send(AppExit::Success);
+}
+
+fn print_summary(
+    run: &HeadlessRun,
+    game_state: &MainGameState,
+    player_query: &Query<&BodyHandle, With<Player>>,
+    enemy_query: &Query<&Enemy>,
+    world: &PhysicsWorld,
+    simulation: &CosmologicalSimulation,
+    reason: &str,
+) {
+    println!("Headless run finished ({}) after {} ticks in {:.2?}", reason, run.ticks_run, run.started.elapsed());
+    println!("  run time:  {:.2}s", game_state.run_time);
+    println!("  score:     {}", game_state.score);
+    println!("  hp:        {:.1}", game_state.player_hp);
+    println!("  shield:    {:.1}", game_state.player_shield);
+    println!("  energy:    {:.1}", game_state.player_energy);
+    println!("  enemies:   {}", enemy_query.iter().count());
+    if let Some(player_obj) = player_query.get_single().ok().and_then(|handle| world.body(*handle)) {
+        println!("  player:    pos ({:.1}, {:.1}) vel ({:.1}, {:.1})",
+                 player_obj.position.x, player_obj.position.y, player_obj.velocity.x, player_obj.velocity.y);
+    }
+    println!("  particles: {} at sim time {:.2}", simulation.get_particle_count(), simulation.get_time());
+}
@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use bevy::sprite::Anchor;
 use crate::actors::player::Player;
-use crate::state::MainGameState;
+use crate::state::{GameMode, MainGameState, PauseState};
 
 #[derive(Component)]
 pub struct HpBar;
@@ -15,6 +15,9 @@ pub struct ShieldBar;
 #[derive(Component)]
 pub struct ScoreCounter;
 
+#[derive(Component)]
+pub struct ModeBanner;
+
 pub fn setup_hud(
     mut commands: Commands,
     window_query: Query<&Window>,
@@ -108,4 +111,52 @@ pub fn update_score(
 ) {
     let mut score_text = query.get_single_mut().unwrap();
     score_text.0 = format!("Score: {:?}", game_state.score);
-}
\ No newline at end of file
+}
+
+pub fn spawn_menu_banner(
+    mut commands: Commands,
+) {
+    spawn_mode_banner(&mut commands, "I Am Black Hole\nPress Enter to start\nF2 to edit the level", GameMode::Menu);
+}
+
+pub fn spawn_game_over_banner(
+    mut commands: Commands,
+    game_state: Res<MainGameState>,
+) {
+    let text = format!("Game Over\nScore: {}\nPress Enter to restart", game_state.score);
+    spawn_mode_banner(&mut commands, &text, GameMode::GameOver);
+}
+
+pub fn spawn_pause_banner(
+    mut commands: Commands,
+) {
+    spawn_mode_banner(&mut commands, "Paused\nPress Esc to resume", PauseState::Paused);
+}
+
+// Screen space banner that is despawned automatically when leaving `mode`
+fn spawn_mode_banner<S: States>(
+    commands: &mut Commands,
+    text: &str,
+    mode: S,
+) {
+    commands
+        .spawn((
+            ModeBanner,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            StateScoped(mode),
+        ))
+        .with_child((
+            Text::new(text),
+            TextFont {
+                font_size: 48.0,
+                ..Default::default()
+            },
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+}
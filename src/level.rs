@@ -0,0 +1,533 @@
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use bevy::asset::io::file::FileAssetReader;
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::actors::particles::SimulationConfig;
+use crate::actors::player::PlayerStart;
+use crate::actors::space_time_rip::{rip_z_position, spawn_space_time_rip, SpaceTimeRipMesh};
+use crate::props::wall_base::{RipLifecycle, RipParams, Wall, WallMotion, WallPath, WallShape, WallSurface};
+use crate::props::walls::{spawn_wall_entity, WallAssets, WallOrder};
+use crate::level_generator::{generate_walls, GeneratorSettings};
+use crate::state::{Difficulty, EnemySpawnRules, GameRng};
+
+pub const DEFAULT_LEVEL_PATH: &str = "levels/default.level.ron";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WallSpec {
+    pub position: (f32, f32),
+    pub size: (f32, f32),
+    pub rotation_degrees: f32,
+    pub shape: WallShape,
+    // Only used by solid walls, rips don't bounce anything
+    #[serde(default)]
+    pub surface: WallSurface,
+    // Only used by rips. `influence_distance` is measured out from the rip's line and `pull_scale` multiplies the
+    // tuning file's pull. Files still using the old `collision_width` (the rip's length) or the absolute
+    // `pull_strength` from before the fixed step are rejected rather than read with the new meaning
+    #[serde(default)]
+    pub rip: RipParams,
+    // Path around the position and rotation above, walls without one stay put
+    #[serde(default)]
+    pub motion: Option<WallMotion>,
+    // Links this rip to the one other rip with the same id
+    #[serde(default)]
+    pub portal: Option<u32>,
+}
+
+impl WallSpec {
+    pub fn to_wall(&self) -> Wall {
+        let (x, y) = self.position;
+        let (width, height) = self.size;
+        let rotation = self.rotation_degrees.to_radians();
+        let mut wall = match self.shape {
+            WallShape::Rigid => Wall::new_rigid(x, y, width, height, rotation),
+            WallShape::SpaceTimeRip => Wall::new_space_time_rip(x, y, width, height, rotation),
+            WallShape::Convex(_) | WallShape::Concave(_) => {
+                Wall::new_curved(x, y, width, height, rotation, self.shape.clone())
+            }
+        };
+        wall.surface = self.surface;
+        wall.rip_params = self.rip;
+        wall.path = self.motion.map(|motion| WallPath {
+            home: self.position,
+            home_rotation: rotation,
+            motion,
+        });
+        wall.portal_pair = self.portal;
+        wall
+    }
+
+    // Moving walls are saved at the home of their path rather than wherever they are right now
+    pub fn from_wall(wall: &Wall) -> Self {
+        let (position, rotation) = match wall.path {
+            Some(path) => (path.home, path.home_rotation),
+            None => ((wall.center_x, wall.center_y), wall.rotation_angle),
+        };
+        WallSpec {
+            position,
+            size: (wall.width, wall.height),
+            rotation_degrees: rotation.to_degrees(),
+            shape: wall.wall_shape.clone(),
+            surface: wall.surface,
+            rip: wall.rip_params,
+            motion: wall.path.map(|path| path.motion),
+            portal: wall.portal_pair,
+        }
+    }
+}
+
+/// Overrides for the particle backdrop, anything left out comes from the command line or the defaults
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ParticleSettings {
+    pub num_particles: Option<usize>,
+    pub theta: Option<f64>,
+    pub g: Option<f64>,
+}
+
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Debug)]
+pub struct Level {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "default_player_start")]
+    pub player_start: (f32, f32),
+    // Falls back to the spawn values in the tuning file when left out
+    #[serde(default)]
+    pub enemy_spawns: Option<EnemySpawnRules>,
+    #[serde(default)]
+    pub particles: ParticleSettings,
+    pub walls: Vec<WallSpec>,
+}
+
+fn default_player_start() -> (f32, f32) {
+    let start = PlayerStart::default().0;
+    (start.x, start.y)
+}
+
+/// A level that failed to parse or validate, `entry` names the part of the file at fault
+#[derive(Debug)]
+pub struct LevelError {
+    pub entry: String,
+    pub message: String,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.entry, self.message)
+    }
+}
+
+impl LevelError {
+    fn new(entry: impl Into<String>, message: impl Into<String>) -> Self {
+        LevelError {
+            entry: entry.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Level {
+    pub fn parse(bytes: &[u8]) -> Result<Self, LevelError> {
+        let level: Level = ron::de::from_bytes(bytes).map_err(|e| {
+            LevelError::new(format!("line {}, column {}", e.position.line, e.position.col), e.code.to_string())
+        })?;
+        level.validate()?;
+        Ok(level)
+    }
+
+    pub fn validate(&self) -> Result<(), LevelError> {
+        let (start_x, start_y) = self.player_start;
+        if !start_x.is_finite() || !start_y.is_finite() {
+            return Err(LevelError::new("player_start", "position must be finite"));
+        }
+
+        for (index, spec) in self.walls.iter().enumerate() {
+            spec.validate().map_err(|message| {
+                LevelError::new(format!("walls[{}] at {:?}", index, spec.position), message)
+            })?;
+        }
+
+        self.validate_portals()?;
+
+        if let Some(rules) = &self.enemy_spawns {
+            rules.validate().map_err(|message| LevelError::new("enemy_spawns", message))?;
+        }
+
+        validate_particles(&self.particles).map_err(|message| LevelError::new("particles", message))
+    }
+
+    // Every portal id has to link exactly two rips
+    fn validate_portals(&self) -> Result<(), LevelError> {
+        for (index, spec) in self.walls.iter().enumerate() {
+            let Some(pair) = spec.portal else {
+                continue;
+            };
+            let entry = || format!("walls[{}] at {:?}", index, spec.position);
+            if !matches!(spec.shape, WallShape::SpaceTimeRip) {
+                return Err(LevelError::new(entry(), "portals are only allowed on SpaceTimeRip walls"));
+            }
+            let count = self.walls.iter().filter(|other| other.portal == Some(pair)).count();
+            if count != 2 {
+                return Err(LevelError::new(entry(), format!("portal {} links {} rips, it needs exactly 2", pair, count)));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn player_start(&self) -> PlayerStart {
+        let (x, y) = self.player_start;
+        PlayerStart(Vec2::new(x, y))
+    }
+
+    // Applies the level's particle overrides under the command line's explicit values
+    pub fn simulation_config(&self, cli_overrides: &ParticleSettings) -> SimulationConfig {
+        let default = SimulationConfig::default();
+        SimulationConfig {
+            num_particles: cli_overrides.num_particles
+                .or(self.particles.num_particles)
+                .unwrap_or(default.num_particles),
+            theta: cli_overrides.theta.or(self.particles.theta).unwrap_or(default.theta),
+            g: cli_overrides.g.or(self.particles.g).unwrap_or(default.g),
+        }
+    }
+}
+
+impl WallSpec {
+    fn validate(&self) -> Result<(), String> {
+        let (x, y) = self.position;
+        let (width, height) = self.size;
+        if !x.is_finite() || !y.is_finite() {
+            return Err("position must be finite".into());
+        }
+        if !(width.is_finite() && width > 0.0 && height.is_finite() && height > 0.0) {
+            return Err(format!("size must be positive, got ({}, {})", width, height));
+        }
+        if !self.rotation_degrees.is_finite() {
+            return Err("rotation_degrees must be finite".into());
+        }
+
+        match self.shape {
+            WallShape::Convex(curvature) | WallShape::Concave(curvature) => {
+                if !(curvature.is_finite() && curvature > 0.0) {
+                    return Err(format!("curvature must be positive, got {}", curvature));
+                }
+            }
+            WallShape::Rigid | WallShape::SpaceTimeRip => {}
+        }
+
+        if !(0.0..=2.0).contains(&self.surface.restitution) {
+            return Err(format!("restitution must be within 0..=2, got {}", self.surface.restitution));
+        }
+        if !(0.0..=1.0).contains(&self.surface.friction) {
+            return Err(format!("friction must be within 0..=1, got {}", self.surface.friction));
+        }
+
+        let rip_values = [self.rip.influence_distance, self.rip.pull_scale, self.rip.energy_drain, self.rip.shield_damage];
+        if rip_values.iter().flatten().any(|value| !(value.is_finite() && *value >= 0.0)) {
+            return Err("rip values must be non-negative".into());
+        }
+        let has_rip_values = rip_values.iter().any(Option::is_some) || self.rip.lifecycle.is_some();
+        if has_rip_values && !matches!(self.shape, WallShape::SpaceTimeRip) {
+            return Err("rip values are only allowed on SpaceTimeRip walls".into());
+        }
+        if let Some(lifecycle) = self.rip.lifecycle {
+            validate_lifecycle(lifecycle)?;
+        }
+
+        if let Some(motion) = self.motion {
+            validate_motion(motion)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_motion(motion: WallMotion) -> Result<(), String> {
+    match motion {
+        WallMotion::PingPong { offset, period } => {
+            if !offset.0.is_finite() || !offset.1.is_finite() {
+                return Err("ping-pong offset must be finite".into());
+            }
+            validate_period(period)
+        }
+        WallMotion::Orbit { radius, period } => {
+            if !(radius.is_finite() && radius >= 0.0) {
+                return Err(format!("orbit radius must be non-negative, got {}", radius));
+            }
+            validate_period(period)
+        }
+        WallMotion::Spin { degrees_per_second } => {
+            if !degrees_per_second.is_finite() {
+                return Err("spin speed must be finite".into());
+            }
+            Ok(())
+        }
+    }
+}
+
+fn validate_lifecycle(lifecycle: RipLifecycle) -> Result<(), String> {
+    if !(lifecycle.open_at.is_finite() && lifecycle.open_at >= 0.0) {
+        return Err(format!("open_at must be non-negative, got {}", lifecycle.open_at));
+    }
+    let durations = [lifecycle.open_duration, lifecycle.grow_duration, lifecycle.collapse_duration];
+    if durations.iter().any(|duration| !(duration.is_finite() && *duration >= 0.0)) {
+        return Err("lifecycle durations must be non-negative".into());
+    }
+    if !(lifecycle.max_growth.is_finite() && lifecycle.max_growth >= 1.0) {
+        return Err(format!("max_growth must be at least 1, got {}", lifecycle.max_growth));
+    }
+    Ok(())
+}
+
+fn validate_period(period: f32) -> Result<(), String> {
+    if !(period.is_finite() && period > 0.0) {
+        return Err(format!("period must be positive, got {}", period));
+    }
+    Ok(())
+}
+
+// Same limits as the matching command line flags
+fn validate_particles(particles: &ParticleSettings) -> Result<(), String> {
+    if let Some(num_particles) = particles.num_particles {
+        if !(1..=250_000).contains(&num_particles) {
+            return Err(format!("num_particles must be within 1..=250000, got {}", num_particles));
+        }
+    }
+    if let Some(theta) = particles.theta {
+        if !(theta > 0.0 && theta <= 2.0) {
+            return Err(format!("theta must be within (0, 2], got {}", theta));
+        }
+    }
+    if let Some(g) = particles.g {
+        if !(g.is_finite() && g > 0.0) {
+            return Err(format!("g must be positive, got {}", g));
+        }
+    }
+    Ok(())
+}
+
+fn asset_file_path(path: &str) -> PathBuf {
+    FileAssetReader::get_base_path().join("assets").join(path)
+}
+
+// Reads a level straight from the assets folder. The particle settings are needed before the
+// asset server has loaded anything, so the startup values come from here
+pub fn read_level(path: &str) -> Result<Level, String> {
+    let full_path = asset_file_path(path);
+    let bytes = fs::read(&full_path).map_err(|e| format!("{}: {}", full_path.display(), e))?;
+    Level::parse(&bytes).map_err(|e| e.to_string())
+}
+
+// Writes a level back into the assets folder. With the `dev` feature the asset watcher then reloads it like any other edit
+pub fn write_level(path: &str, level: &Level) -> Result<(), String> {
+    level.validate().map_err(|e| e.to_string())?;
+    let config = ron::ser::PrettyConfig::new()
+        .extensions(ron::extensions::Extensions::IMPLICIT_SOME);
+    let contents = ron::ser::to_string_pretty(level, config).map_err(|e| e.to_string())?;
+    let full_path = asset_file_path(path);
+    fs::write(&full_path, contents).map_err(|e| format!("{}: {}", full_path.display(), e))
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = String;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await.map_err(|e| e.to_string())?;
+        Level::parse(&bytes)
+            .map_err(|e| format!("{}: {}", load_context.path().display(), e))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+#[derive(Resource)]
+pub struct LevelPath(pub String);
+
+#[derive(Resource)]
+pub struct LevelHandle(pub Handle<Level>);
+
+/// Present once the level's walls have been spawned, runs don't start before that
+#[derive(Resource)]
+pub struct LevelApplied;
+
+/// Spawns the walls and their rips from the level asset and applies its player start and enemy spawn rules.
+/// With `GeneratorSettings` present the walls are generated from the run's seed instead.
+/// Edits to the file are picked up live, the particle settings and player start take effect on the next run
+pub struct LevelPlugin {
+    pub path: String,
+}
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .init_resource::<PlayerStart>()
+            .insert_resource(LevelPath(self.path.clone()))
+            .add_systems(PreStartup, load_level)
+            .add_systems(PreUpdate, apply_level);
+    }
+}
+
+fn load_level(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    path: Res<LevelPath>,
+) {
+    commands.insert_resource(LevelHandle(asset_server.load(path.0.clone())));
+}
+
+// Whatever a previous application of the level spawned
+type LevelEntities = Or<(With<Wall>, With<SpaceTimeRipMesh>)>;
+
+// What a generated layout is made from, when the generator is switched on
+#[derive(SystemParam)]
+struct LayoutGenerator<'w> {
+    settings: Option<Res<'w, GeneratorSettings>>,
+    rng: Res<'w, GameRng>,
+    difficulty: Res<'w, Difficulty>,
+}
+
+fn apply_level(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<Level>>,
+    mut assets: WallAssets,
+    levels: Res<Assets<Level>>,
+    handle: Res<LevelHandle>,
+    generator: LayoutGenerator,
+    existing_query: Query<Entity, LevelEntities>,
+) {
+    let changed = events.read()
+        .any(|event| event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0));
+    if !changed {
+        return;
+    }
+    let Some(level) = levels.get(&handle.0) else {
+        return;
+    };
+
+    let player_start = level.player_start();
+    commands.insert_resource(player_start);
+    match &level.enemy_spawns {
+        Some(rules) => commands.insert_resource(rules.clone()),
+        None => commands.remove_resource::<EnemySpawnRules>(),
+    }
+
+    for entity in existing_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    // A generated layout takes the place of the file's walls, the rest of the level still applies
+    let walls = match &generator.settings {
+        Some(settings) => generate_walls(settings, generator.rng.seed(), generator.difficulty.0, player_start.0),
+        None => {
+            info!("Spawning {} walls from level '{}'", level.walls.len(), level.name);
+            level.walls.clone()
+        }
+    };
+    spawn_level_walls(&mut commands, &mut assets, &walls);
+    commands.insert_resource(LevelApplied);
+}
+
+// Spawns the wall entities and their rips for a list of wall specs, from a level file or the generator
+pub fn spawn_level_walls(
+    commands: &mut Commands,
+    assets: &mut WallAssets,
+    walls: &[WallSpec],
+) {
+    for (index, spec) in walls.iter().enumerate() {
+        let wall = spec.to_wall();
+        let wall_entity = spawn_wall_entity(commands, &mut assets.meshes, &mut assets.materials, wall.clone(), WallOrder(index as u32));
+        if matches!(spec.shape, WallShape::SpaceTimeRip) {
+            let z_position = rip_z_position(index, walls.len());
+            spawn_space_time_rip(commands, assets, wall_entity, &wall, z_position, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level_with_walls(walls: &str) -> Result<Level, LevelError> {
+        Level::parse(format!("#![enable(implicit_some)]\n(name: \"test\", walls: [{}])", walls).as_bytes())
+    }
+
+    fn rip(x: f32, portal: u32) -> String {
+        format!("(position: ({}, 0.0), size: (100.0, 10.0), rotation_degrees: 0.0, shape: SpaceTimeRip, portal: {}),", x, portal)
+    }
+
+    #[test]
+    fn portal_linking_two_rips_is_valid() {
+        let level = level_with_walls(&(rip(0.0, 1) + &rip(500.0, 1)));
+        assert!(level.is_ok());
+    }
+
+    #[test]
+    fn portal_id_used_three_times_is_rejected() {
+        let error = level_with_walls(&(rip(0.0, 1) + &rip(500.0, 1) + &rip(1000.0, 1))).unwrap_err();
+        assert_eq!(error.entry, "walls[0] at (0.0, 0.0)");
+        assert!(error.message.contains("links 3 rips"), "{}", error.message);
+    }
+
+    #[test]
+    fn portal_without_a_partner_is_rejected() {
+        let error = level_with_walls(&(rip(0.0, 1) + &rip(500.0, 2))).unwrap_err();
+        assert!(error.message.contains("links 1 rips"), "{}", error.message);
+    }
+
+    #[test]
+    fn portal_on_a_solid_wall_is_rejected() {
+        let walls = rip(0.0, 1)
+            + "(position: (500.0, 0.0), size: (100.0, 10.0), rotation_degrees: 0.0, shape: Rigid, portal: 1),";
+        let error = level_with_walls(&walls).unwrap_err();
+        assert_eq!(error.entry, "walls[1] at (500.0, 0.0)");
+    }
+
+    #[test]
+    fn rip_values_on_a_solid_wall_are_rejected() {
+        let walls = "(position: (0.0, 0.0), size: (100.0, 10.0), rotation_degrees: 0.0, shape: Rigid, rip: (pull_scale: 2.0)),";
+        assert!(level_with_walls(walls).is_err());
+    }
+
+    #[test]
+    fn rip_values_from_before_the_rename_are_rejected() {
+        for old_field in ["collision_width: 300.0", "pull_strength: 100.0"] {
+            let walls = format!(
+                "(position: (0.0, 0.0), size: (100.0, 10.0), rotation_degrees: 0.0, shape: SpaceTimeRip, rip: ({})),",
+                old_field
+            );
+            assert!(level_with_walls(&walls).is_err(), "{} was accepted", old_field);
+        }
+    }
+
+    #[test]
+    fn lifecycle_with_negative_duration_is_rejected() {
+        let walls = "(position: (0.0, 0.0), size: (100.0, 10.0), rotation_degrees: 0.0, shape: SpaceTimeRip, \
+            rip: (lifecycle: (open_at: 1.0, open_duration: -1.0, grow_duration: 1.0, collapse_duration: 1.0, max_growth: 1.5))),";
+        assert!(level_with_walls(walls).is_err());
+    }
+
+    #[test]
+    fn spawn_speeds_upside_down_are_rejected() {
+        let level = Level::parse(b"(walls: [], enemy_spawns: Some((min_spawn_speed: 300.0, max_spawn_speed: 100.0)))");
+        assert_eq!(level.unwrap_err().entry, "enemy_spawns");
+    }
+}
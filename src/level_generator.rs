@@ -0,0 +1,260 @@
+use bevy::prelude::*;
+use clap::ValueEnum;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use crate::actors::player::PLAYER_RADIUS;
+use crate::actors::space_time_rip::closest_point_on_segment;
+use crate::level::WallSpec;
+use crate::props::wall_base::{RipParams, WallShape, WallSurface};
+
+// Mixed into the run seed, so the layout doesn't share its sequence with `GameRng`
+const GENERATOR_SEED_SALT: u64 = 0x5eed_1e7e1;
+
+/// Where the generated walls are scattered around
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum GeneratorCenter {
+    Origin,
+    Player,
+}
+
+/// Replaces the level's walls with a generated layout
+#[derive(Resource, Clone, Debug)]
+pub struct GeneratorSettings {
+    pub center: GeneratorCenter,
+    pub radius: f32,
+    pub wall_count: usize,
+    pub min_length: f32,
+    pub max_length: f32,
+    // Smallest gap between two walls, never less than the corridor the player needs
+    pub min_spacing: f32,
+    // Nothing is placed closer than this to the player's start
+    pub spawn_clearance: f32,
+    // Extra room around the player's diameter that every gap has to leave
+    pub corridor_margin: f32,
+    // Attempts per wall before giving up on it
+    pub max_attempts: usize,
+}
+
+impl GeneratorSettings {
+    pub fn new(center: GeneratorCenter) -> Self {
+        GeneratorSettings {
+            center,
+            radius: 1200.0,
+            wall_count: 14,
+            min_length: 150.0,
+            max_length: 400.0,
+            min_spacing: 120.0,
+            spawn_clearance: 250.0,
+            corridor_margin: 20.0,
+            max_attempts: 40,
+        }
+    }
+
+    // Gaps between walls are at least this wide, so the player fits through each of them
+    fn corridor_width(&self) -> f32 {
+        (2.0 * PLAYER_RADIUS + self.corridor_margin).max(self.min_spacing)
+    }
+}
+
+// Share of the generated walls that become rips, more of them on higher difficulties
+fn rip_density(difficulty: f32) -> f64 {
+    (0.2 + 0.15 * difficulty as f64).clamp(0.1, 0.9)
+}
+
+/// Places walls at random within `radius` of the center, rejecting any that come too close to the player's start
+/// or to another wall. Every gap stays at least a corridor wide, so no part of the layout is sealed off
+pub fn generate_walls(
+    settings: &GeneratorSettings,
+    seed: u64,
+    difficulty: f32,
+    player_start: Vec2,
+) -> Vec<WallSpec> {
+    let mut rng = StdRng::seed_from_u64(seed ^ GENERATOR_SEED_SALT);
+    let center = match settings.center {
+        GeneratorCenter::Origin => Vec2::ZERO,
+        GeneratorCenter::Player => player_start,
+    };
+    let corridor_width = settings.corridor_width();
+    let rip_chance = rip_density(difficulty);
+
+    let mut walls: Vec<WallSpec> = Vec::with_capacity(settings.wall_count);
+    for _ in 0..settings.wall_count {
+        for _ in 0..settings.max_attempts {
+            let candidate = random_wall(&mut rng, settings, center, rip_chance);
+            let footprint = WallFootprint::of(&candidate);
+
+            if footprint.distance_to_point(player_start) < settings.spawn_clearance + PLAYER_RADIUS {
+                continue;
+            }
+            let crowded = walls.iter()
+                .any(|wall| footprint.distance_to(&WallFootprint::of(wall)) < corridor_width);
+            if crowded {
+                continue;
+            }
+
+            walls.push(candidate);
+            break;
+        }
+    }
+
+    info!("Generated {} of {} walls from seed {}", walls.len(), settings.wall_count, seed);
+    walls
+}
+
+fn random_wall(
+    rng: &mut StdRng,
+    settings: &GeneratorSettings,
+    center: Vec2,
+    rip_chance: f64,
+) -> WallSpec {
+    // Uniform over the disc rather than bunched up in the middle
+    let distance = settings.radius * rng.random::<f32>().sqrt();
+    let angle = rng.random_range(0.0..std::f32::consts::TAU);
+    let position = center + Vec2::from_angle(angle) * distance;
+    let length = rng.random_range(settings.min_length..=settings.max_length);
+    let rotation_degrees = rng.random_range(0.0..180.0);
+
+    let (shape, thickness) = if rng.random_bool(rip_chance) {
+        (WallShape::SpaceTimeRip, 10.0)
+    } else {
+        // Mostly straight barriers with the odd deflector, curved no tighter than a half circle
+        let curvature = rng.random_range(0.5..1.5) / length;
+        let shape = match rng.random_range(0..4) {
+            0 => WallShape::Convex(curvature),
+            1 => WallShape::Concave(curvature),
+            _ => WallShape::Rigid,
+        };
+        (shape, 16.0)
+    };
+
+    WallSpec {
+        position: (position.x, position.y),
+        size: (length, thickness),
+        rotation_degrees,
+        shape,
+        surface: WallSurface::default(),
+        rip: RipParams::default(),
+        motion: None,
+        portal: None,
+    }
+}
+
+// A wall reduced to its chord and how far its body reaches from it, enough for spacing checks
+struct WallFootprint {
+    start: Vec2,
+    end: Vec2,
+    reach: f32,
+}
+
+impl WallFootprint {
+    fn of(spec: &WallSpec) -> Self {
+        let (width, height) = spec.size;
+        let wall = spec.to_wall();
+        // A curved wall bulges away from its chord by the arc's sagitta
+        let bulge = wall.arc()
+            .map(|arc| arc.radius - (arc.radius * arc.radius - width * width / 4.0).max(0.0).sqrt())
+            .unwrap_or(0.0);
+        let direction = Vec2::from_angle(spec.rotation_degrees.to_radians());
+        let center = Vec2::new(spec.position.0, spec.position.1);
+        WallFootprint {
+            start: center - direction * width / 2.0,
+            end: center + direction * width / 2.0,
+            reach: height / 2.0 + bulge,
+        }
+    }
+
+    fn distance_to_point(&self, point: Vec2) -> f32 {
+        (distance_point_segment(point, self.start, self.end) - self.reach).max(0.0)
+    }
+
+    fn distance_to(&self, other: &WallFootprint) -> f32 {
+        let chord_distance = if segments_intersect(self.start, self.end, other.start, other.end) {
+            0.0
+        } else {
+            distance_point_segment(self.start, other.start, other.end)
+                .min(distance_point_segment(self.end, other.start, other.end))
+                .min(distance_point_segment(other.start, self.start, self.end))
+                .min(distance_point_segment(other.end, self.start, self.end))
+        };
+        (chord_distance - self.reach - other.reach).max(0.0)
+    }
+}
+
+fn distance_point_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    point.distance(closest_point_on_segment(point, start, end))
+}
+
+fn segments_intersect(a1: Vec2, a2: Vec2, b1: Vec2, b2: Vec2) -> bool {
+    let d1 = (a2 - a1).perp_dot(b1 - a1);
+    let d2 = (a2 - a1).perp_dot(b2 - a1);
+    let d3 = (b2 - b1).perp_dot(a1 - b1);
+    let d4 = (b2 - b1).perp_dot(a2 - b1);
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAYER_START: Vec2 = Vec2::new(300.0, -200.0);
+
+    fn layout(center: GeneratorCenter, seed: u64) -> Vec<WallSpec> {
+        generate_walls(&GeneratorSettings::new(center), seed, 1.0, PLAYER_START)
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_layout() {
+        let first = layout(GeneratorCenter::Origin, 7);
+        let second = layout(GeneratorCenter::Origin, 7);
+
+        assert!(!first.is_empty());
+        assert_eq!(format!("{:?}", first), format!("{:?}", second));
+    }
+
+    #[test]
+    fn different_seeds_give_different_layouts() {
+        let first = layout(GeneratorCenter::Origin, 7);
+        let second = layout(GeneratorCenter::Origin, 8);
+
+        assert_ne!(format!("{:?}", first), format!("{:?}", second));
+    }
+
+    #[test]
+    fn every_gap_leaves_a_corridor() {
+        let settings = GeneratorSettings::new(GeneratorCenter::Origin);
+        for seed in 0..20 {
+            let footprints: Vec<WallFootprint> = generate_walls(&settings, seed, 1.0, PLAYER_START)
+                .iter()
+                .map(WallFootprint::of)
+                .collect();
+            for (index, footprint) in footprints.iter().enumerate() {
+                for other in &footprints[index + 1..] {
+                    assert!(footprint.distance_to(other) >= settings.corridor_width(), "seed {}", seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn nothing_is_placed_near_the_player_start() {
+        for center in [GeneratorCenter::Origin, GeneratorCenter::Player] {
+            let settings = GeneratorSettings::new(center);
+            for seed in 0..20 {
+                for wall in generate_walls(&settings, seed, 1.0, PLAYER_START) {
+                    let distance = WallFootprint::of(&wall).distance_to_point(PLAYER_START);
+                    assert!(distance >= settings.spawn_clearance + PLAYER_RADIUS, "seed {}", seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn walls_stay_within_the_radius() {
+        let settings = GeneratorSettings::new(GeneratorCenter::Player);
+        for wall in generate_walls(&settings, 3, 1.0, PLAYER_START) {
+            let position = Vec2::new(wall.position.0, wall.position.1);
+            assert!(position.distance(PLAYER_START) <= settings.radius + 1.0e-3);
+        }
+    }
+}
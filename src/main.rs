@@ -1,28 +1,164 @@
 mod actors;
+mod cli;
 mod state;
 mod hud;
 mod props;
 mod window_plugin;
+mod headless;
+mod render_backend;
+mod level;
+mod editor;
+mod level_generator;
+mod spatial_index;
 
 use bevy::prelude::*;
 use bevy::render::RenderPlugin;
-use bevy::render::settings::{Backends, MemoryHints, RenderCreation, WgpuSettings};
+use bevy::render::settings::{MemoryHints, RenderCreation, WgpuSettings};
+use clap::Parser;
 use crate::actors::black_hole::{BlackHolePlugin};
 use crate::actors::distortion::{DistortionPostProcessPlugin};
+use crate::cli::Cli;
+use crate::editor::EditorPlugin;
+use crate::headless::HeadlessPlugin;
+use crate::level::LevelPlugin;
 use crate::props::walls::WallsPlugin;
-use crate::window_plugin::{CustomWindowPlugin, WindowConfig};
+use crate::state::{GameMode, GameplaySet, PauseState};
+use crate::window_plugin::CustomWindowPlugin;
 
 fn main() {
+    let mut cli = Cli::parse();
     let mut app = App::new();
-    let default_window_config = WindowConfig::default();
-    let window_plugin = CustomWindowPlugin::new(default_window_config);
+
+    // A replay must run with the settings it was recorded with, whatever the command line says
+    let replay_mode = state::replay_mode_from_paths(cli.record.clone(), cli.replay.clone());
+    if let Some(settings) = replay_mode.settings() {
+        cli.use_replay_settings(settings);
+        app.insert_resource(settings.tuning.player.clone())
+            .insert_resource(settings.tuning.enemy.clone())
+            .insert_resource(settings.tuning.rip.clone())
+            .insert_resource(state::TuningApplied);
+    }
+    let seed = state::resolve_seed(cli.seed);
+
+    match cli.headless {
+        Some(ticks) => {
+            app.add_plugins(HeadlessPlugin { ticks });
+        }
+        None => add_windowed_plugins(&mut app, &cli),
+    }
+    app.init_state::<GameMode>();
+
+    // The walls follow once the asset server has the level, everything needed at startup is read right away
+    let level = level::read_level(&cli.level)
+        .unwrap_or_else(|e| panic!("Failed to load level {}: {}", cli.level, e));
+    if let Some(spawn_rules) = level.enemy_spawns.clone() {
+        app.insert_resource(spawn_rules);
+    }
+    if let Some(center) = cli.generate {
+        app.insert_resource(level_generator::GeneratorSettings::new(center));
+    }
+
+    app.insert_resource(state::GameRng::new(seed))
+        .insert_resource(state::Difficulty(cli.difficulty))
+        .insert_resource(level.simulation_config(&cli.particle_overrides()))
+        .insert_resource(level.player_start())
+        .insert_resource(replay_mode)
+        .init_resource::<state::PlayerInputFrame>()
+        .init_resource::<state::BoostLatch>()
+        .init_resource::<actors::physics_world::PhysicsWorld>()
+        .init_resource::<spatial_index::SpatialIndex>();
+    app.add_plugins((state::TuningPlugin, LevelPlugin { path: cli.level.clone() }));
+    app.add_sub_state::<PauseState>()
+        .enable_state_scoped_entities::<GameMode>()
+        .enable_state_scoped_entities::<PauseState>();
+
+    app
+        .add_systems(PreStartup, (
+            state::setup_game_state,
+            actors::particles::setup,
+        ))
+        .add_systems(Startup, (state::log_seed, actors::player::setup_player))
+        .add_systems(OnExit(GameMode::GameOver), (state::restart_run, state::respawn_actors))
+        .add_systems(OnEnter(PauseState::Paused), state::pause_time)
+        .add_systems(OnExit(PauseState::Paused), state::resume_time)
+        .add_systems(OnEnter(GameMode::GameOver), state::save_recording)
+        .add_systems(Update, state::start_replay
+            .run_if(in_state(GameMode::Menu))
+            .run_if(state::run_assets_ready))
+        .configure_sets(FixedUpdate, (
+            GameplaySet::Input,
+            GameplaySet::Physics,
+            GameplaySet::Interactions,
+        ).chain())
+        .add_systems(PreUpdate, state::latch_boost_input)
+        .add_systems(FixedUpdate, (
+            actors::interpolation::begin_interpolation_tick,
+            state::advance_run_time,
+            state::sample_player_input,
+            actors::player::player_input,
+        ).chain().in_set(GameplaySet::Input).run_if(in_state(PauseState::Running)))
+        .add_systems(FixedUpdate, (
+            props::walls::move_walls,
+            actors::enemy::apply_enemy_forces,
+        ).chain()
+            .in_set(GameplaySet::Physics)
+            .before(actors::physics_world::step_physics_world)
+            .run_if(in_state(PauseState::Running)))
+        .add_systems(FixedUpdate, (
+            actors::physics_world::step_physics_world,
+            actors::player::player_movement_physics,
+        ).chain()
+            .in_set(GameplaySet::Physics)
+            .run_if(in_state(PauseState::Running)))
+        .add_event::<actors::enemy::EnemyContact>()
+        .add_systems(FixedUpdate, (
+            spatial_index::rebuild_spatial_index,
+            actors::enemy::update_enemy,
+            actors::player::update_event_horizon,
+        ).chain()
+            .in_set(GameplaySet::Physics)
+            .after(actors::player::player_movement_physics)
+            .run_if(in_state(PauseState::Running)))
+        .add_systems(FixedUpdate, (
+            actors::enemy::spawn_enemy,
+            state::refresh_player_energy,
+            state::refresh_player_shield,
+        ).chain()
+            .in_set(GameplaySet::Interactions)
+            .after(actors::space_time_rip::detect_rip_collisions)
+            .run_if(in_state(PauseState::Running)))
+        .add_systems(FixedUpdate, (
+            props::walls::detect_wall_collisions,
+            props::walls::detect_enemy_wall_collisions,
+        ).chain()
+            .in_set(GameplaySet::Interactions)
+            .before(actors::space_time_rip::detect_rip_collisions)
+            .run_if(in_state(PauseState::Running)))
+        .add_systems(Update, state::check_game_over.run_if(in_state(PauseState::Running)))
+        .add_systems(Update, actors::particles::update_simulation.run_if(not(in_state(PauseState::Paused))))
+        .add_systems(FixedUpdate, actors::interpolation::record_physics_positions
+            .after(GameplaySet::Interactions)
+            .run_if(in_state(PauseState::Running)))
+        .add_systems(Update, (
+            actors::interpolation::interpolate_body_transforms,
+            actors::interpolation::interpolate_wall_transforms,
+        ).run_if(in_state(PauseState::Running)))
+        .add_systems(PostUpdate, actors::physics_world::release_despawned_bodies)
+        .add_systems(Last, state::save_recording.run_if(on_event::<AppExit>))
+        .run();
+}
+
+// Rendering, HUD and everything else that needs a window
+fn add_windowed_plugins(app: &mut App, cli: &Cli) {
+    let window_plugin = CustomWindowPlugin::new(cli.window_config());
+    let backends = render_backend::select_backends(cli.backend);
 
     app.add_plugins(
         DefaultPlugins
             .set(
                 RenderPlugin {
                     render_creation: RenderCreation::Automatic(WgpuSettings {
-                        backends: Some(Backends::DX12),
+                        backends: Some(backends),
                         memory_hints: MemoryHints::Performance,
                         ..default()
                     }),
@@ -35,38 +171,31 @@ fn main() {
     app.add_plugins(BlackHolePlugin);
     app.add_plugins(DistortionPostProcessPlugin);
     app.add_plugins(WallsPlugin);
+    app.add_plugins(EditorPlugin);
 
 
     app
-        .add_systems(PreStartup, (
-            hud::setup_hud,
-            state::setup_game_state,
-            actors::particles::setup,
-        ))
+        .add_systems(PreStartup, hud::setup_hud)
         .add_systems(Startup, (
             actors::player::setup_camera,
-            actors::player::setup_player,
             actors::particles::spawn_particles,
-            props::walls::spawn_space_time_walls,
-        ))
-        .add_systems(FixedUpdate, (
-            actors::enemy::spawn_enemy,
-            state::refresh_player_energy,
-            state::refresh_player_shield,
-        ))
-        .add_systems(PreUpdate, actors::player::player_movement_physics)
-        .add_systems(Update,(
-            actors::enemy::update_enemy,
-            actors::player::update_player_movement,
-            actors::player::camera_movement,
-            actors::particles::update_simulation,
         ))
+        .add_systems(OnEnter(GameMode::Menu), hud::spawn_menu_banner)
+        .add_systems(OnEnter(GameMode::GameOver), hud::spawn_game_over_banner)
+        .add_systems(OnEnter(PauseState::Paused), hud::spawn_pause_banner)
+        .add_systems(Update, state::start_game
+            .run_if(in_state(GameMode::Menu).or(in_state(GameMode::GameOver)))
+            .run_if(state::run_assets_ready))
+        .add_systems(Update, (
+            state::toggle_pause,
+            state::quick_save,
+            state::quick_load,
+        ).run_if(in_state(GameMode::GameRunning)))
+        .add_systems(Update, actors::player::camera_movement.after(actors::interpolation::interpolate_body_transforms))
         .add_systems(PostUpdate, (
-            actors::player::player_input,
             hud::update_energy,
             hud::update_hp,
             hud::update_shield,
             hud::update_score,
-        ))
-        .run();
+        ));
 }
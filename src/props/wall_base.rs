@@ -1,7 +1,12 @@
 use bevy::prelude::*;
+use rs_physics::interactions::elastic_collision_2d;
 use rs_physics::models::ObjectIn2D;
 use rs_physics::utils::PhysicsConstants;
+use serde::{Deserialize, Serialize};
 
+/// Curved shapes carry their curvature (1 / radius), and bulge along the wall's local +y.
+/// A convex wall faces +y with its outer side, a concave wall faces +y with its hollow
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum WallShape {
     Rigid,
     Convex(f32),
@@ -9,6 +14,11 @@ pub enum WallShape {
     SpaceTimeRip,
 }
 
+// Collisions are worked out in the wall's frame, the wall's velocity is taken off the body's first.
+// There the wall is at rest, and heavy enough that it takes none of the body's momentum
+const IMMOVABLE_WALL_MASS: f64 = 1.0e12;
+
+#[derive(Debug)]
 pub enum WallInteractionError {
     CalculationError(String)
 }
@@ -26,7 +36,7 @@ pub trait WallInteractions {
         duration: f64,
         drag_coefficient: f32,
         cross_sectional_area: f32
-    ) -> Result<(), &'static str>;
+    ) -> Result<(), WallInteractionError>;
 
     /// Helper to find the collision angle of some point on a wall's surface
     fn calculate_wall_face_angle_by_position(
@@ -36,7 +46,161 @@ pub trait WallInteractions {
     ) -> Result<f32, WallInteractionError>;
 }
 
-#[derive(Component)]
+/// Arc traced by the middle of a curved wall, in the wall's local frame.
+/// The wall's width is the chord between the arc's ends and its height is the band's thickness
+pub struct WallArc {
+    pub center: Vec2,
+    pub radius: f32,
+    pub mid_angle: f32,
+    pub half_angle: f32,
+}
+
+/// How a solid wall answers a bounce. Restitution scales the speed leaving the wall along its normal,
+/// friction takes away that fraction of the speed sliding along it
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct WallSurface {
+    pub restitution: f32,
+    pub friction: f32,
+}
+
+impl Default for WallSurface {
+    fn default() -> Self {
+        WallSurface {
+            restitution: 0.8,
+            friction: 0.1,
+        }
+    }
+}
+
+/// Per-rip overrides of the `RipTuning` values, anything left out follows the tuning file.
+/// Unknown fields are an error, so values under an old or misspelled name aren't dropped without a word
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RipParams {
+    // How far from the rip's line its pull and drain reach. Replaces `collision_width`, which was the rip's length
+    pub influence_distance: Option<f32>,
+    // Multiple of the tuning file's `pull_strength`. Replaces the absolute `pull_strength` override, whose units
+    // changed when the forces moved to the fixed step
+    pub pull_scale: Option<f32>,
+    pub energy_drain: Option<f32>,
+    pub shield_damage: Option<f32>,
+    // Rips with a lifecycle tear open during the run and collapse again, the others stay open throughout
+    pub lifecycle: Option<RipLifecycle>,
+}
+
+/// When a rip tears open and how long each of its phases lasts, in seconds of run time
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct RipLifecycle {
+    pub open_at: f32,
+    pub open_duration: f32,
+    pub grow_duration: f32,
+    pub collapse_duration: f32,
+    // Length and pull the rip reaches at the end of its growth, relative to its wall
+    pub max_growth: f32,
+}
+
+/// Where a rip is in its lifecycle, with the progress through the current phase from 0 to 1
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RipPhase {
+    // Rips without a lifecycle
+    Open,
+    Dormant,
+    Opening(f32),
+    Growing(f32),
+    Collapsing(f32),
+    Collapsed,
+}
+
+impl RipLifecycle {
+    pub fn phase_at(&self, time: f32) -> RipPhase {
+        let mut age = time - self.open_at;
+        if age < 0.0 {
+            return RipPhase::Dormant;
+        }
+        for (duration, phase) in [
+            (self.open_duration, RipPhase::Opening as fn(f32) -> RipPhase),
+            (self.grow_duration, RipPhase::Growing),
+            (self.collapse_duration, RipPhase::Collapsing),
+        ] {
+            if age < duration {
+                return phase(age / duration);
+            }
+            age -= duration;
+        }
+        RipPhase::Collapsed
+    }
+
+    // Scale of the rip's length and pull during a phase, 1 being the wall as placed
+    pub fn scale(&self, phase: RipPhase) -> f32 {
+        match phase {
+            RipPhase::Open => 1.0,
+            RipPhase::Dormant | RipPhase::Collapsed => 0.0,
+            RipPhase::Opening(progress) => progress,
+            RipPhase::Growing(progress) => 1.0 + (self.max_growth - 1.0) * progress,
+            RipPhase::Collapsing(progress) => self.max_growth * (1.0 - progress),
+        }
+    }
+}
+
+/// Kinematic path a wall follows around its home pose, driven by the run's clock
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum WallMotion {
+    // Eases from home to home + offset and back once per period
+    PingPong { offset: (f32, f32), period: f32 },
+    // Circles its home position counter-clockwise once per period, keeping its rotation
+    Orbit { radius: f32, period: f32 },
+    Spin { degrees_per_second: f32 },
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct WallPath {
+    pub home: (f32, f32),
+    pub home_rotation: f32,
+    pub motion: WallMotion,
+}
+
+/// Where a moving wall is at some point in time, and how fast it is going there
+pub struct WallPose {
+    pub position: Vec2,
+    pub rotation: f32,
+    pub velocity: Vec2,
+    pub angular_velocity: f32,
+}
+
+impl WallPath {
+    pub fn pose_at(&self, time: f32) -> WallPose {
+        let home = Vec2::new(self.home.0, self.home.1);
+        let mut pose = WallPose {
+            position: home,
+            rotation: self.home_rotation,
+            velocity: Vec2::ZERO,
+            angular_velocity: 0.0,
+        };
+
+        match self.motion {
+            WallMotion::PingPong { offset, period } => {
+                let offset = Vec2::new(offset.0, offset.1);
+                let phase = std::f32::consts::TAU * time / period;
+                pose.position = home + offset * (1.0 - phase.cos()) / 2.0;
+                pose.velocity = offset * (std::f32::consts::PI / period) * phase.sin();
+            }
+            WallMotion::Orbit { radius, period } => {
+                let angular_speed = std::f32::consts::TAU / period;
+                let phase = angular_speed * time;
+                pose.position = home + Vec2::from_angle(phase) * radius;
+                pose.velocity = Vec2::from_angle(phase).perp() * radius * angular_speed;
+            }
+            WallMotion::Spin { degrees_per_second } => {
+                pose.angular_velocity = degrees_per_second.to_radians();
+                pose.rotation = self.home_rotation + pose.angular_velocity * time;
+            }
+        }
+        pose
+    }
+}
+
+/// Saved as is in run snapshots, so changing its fields means bumping `SNAPSHOT_VERSION`
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Wall {
     pub center_x: f32,
     pub center_y: f32,
@@ -44,6 +208,16 @@ pub struct Wall {
     pub width: f32,
     pub rotation_angle: f32,
     pub wall_shape: WallShape,
+    pub surface: WallSurface,
+    pub rip_params: RipParams,
+    pub path: Option<WallPath>,
+    // Rips sharing an id are linked, whatever crosses one comes out of the other
+    pub portal_pair: Option<u32>,
+    // Set every tick for walls with a path, collisions hand it on to whatever they hit
+    #[serde(skip)]
+    pub velocity: Vec2,
+    #[serde(skip)]
+    pub angular_velocity: f32,
 }
 
 impl Wall {
@@ -56,6 +230,12 @@ impl Wall {
             width,
             rotation_angle: rotation,
             wall_shape: WallShape::SpaceTimeRip,
+            surface: WallSurface::default(),
+            rip_params: RipParams::default(),
+            path: None,
+            portal_pair: None,
+            velocity: Vec2::ZERO,
+            angular_velocity: 0.0,
         }
     }
 
@@ -68,9 +248,152 @@ impl Wall {
             width,
             rotation_angle: rotation,
             wall_shape: WallShape::Rigid,
+            surface: WallSurface::default(),
+            rip_params: RipParams::default(),
+            path: None,
+            portal_pair: None,
+            velocity: Vec2::ZERO,
+            angular_velocity: 0.0,
+        }
+    }
+
+    // Create a curved wall, `wall_shape` should be `Convex` or `Concave`
+    pub fn new_curved(center_x: f32, center_y: f32, width: f32, height: f32, rotation: f32, wall_shape: WallShape) -> Self {
+        Wall {
+            center_x,
+            center_y,
+            height,
+            width,
+            rotation_angle: rotation,
+            wall_shape,
+            surface: WallSurface::default(),
+            rip_params: RipParams::default(),
+            path: None,
+            portal_pair: None,
+            velocity: Vec2::ZERO,
+            angular_velocity: 0.0,
         }
     }
 
+    // Moves the wall to where its path puts it at `time`, walls without a path stay put
+    pub fn follow_path(&mut self, time: f32) {
+        let Some(path) = self.path else {
+            return;
+        };
+        let pose = path.pose_at(time);
+        self.center_x = pose.position.x;
+        self.center_y = pose.position.y;
+        self.rotation_angle = pose.rotation;
+        self.velocity = pose.velocity;
+        self.angular_velocity = pose.angular_velocity;
+    }
+
+    pub fn pose(&self) -> Isometry2d {
+        Isometry2d::new(Vec2::new(self.center_x, self.center_y), Rot2::radians(self.rotation_angle))
+    }
+
+    // Velocity of the wall's surface at some world position, including its spin
+    pub fn velocity_at(&self, position: Vec2) -> Vec2 {
+        let lever = position - Vec2::new(self.center_x, self.center_y);
+        self.velocity + lever.perp() * self.angular_velocity
+    }
+
+    // Shifts the wall and the home of its path alike, used by the editor
+    pub fn translate(&mut self, delta: Vec2) {
+        self.center_x += delta.x;
+        self.center_y += delta.y;
+        if let Some(path) = &mut self.path {
+            path.home.0 += delta.x;
+            path.home.1 += delta.y;
+        }
+    }
+
+    pub fn rotate(&mut self, delta: f32) {
+        self.rotation_angle += delta;
+        if let Some(path) = &mut self.path {
+            path.home_rotation += delta;
+        }
+    }
+
+    // Line through the middle of the wall along its length, which is where a rip tears
+    pub fn segment(&self) -> (Vec2, Vec2) {
+        (
+            self.to_world(Vec2::new(-self.width / 2.0, 0.0)),
+            self.to_world(Vec2::new(self.width / 2.0, 0.0)),
+        )
+    }
+
+    // Rips pull bodies in rather than stopping them, every other shape is solid
+    pub fn is_solid(&self) -> bool {
+        !matches!(self.wall_shape, WallShape::SpaceTimeRip)
+    }
+
+    // Arc of a curved wall, `None` for flat walls or a curvature that can't form an arc.
+    // The radius never drops below half the width, so the tightest curve is a half circle
+    pub fn arc(&self) -> Option<WallArc> {
+        let (curvature, mid_angle) = match self.wall_shape {
+            WallShape::Convex(curvature) => (curvature, std::f32::consts::FRAC_PI_2),
+            WallShape::Concave(curvature) => (curvature, -std::f32::consts::FRAC_PI_2),
+            _ => return None,
+        };
+        if !curvature.is_finite() || curvature <= 0.0 || self.width <= 0.0 {
+            return None;
+        }
+
+        let half_chord = self.width / 2.0;
+        let radius = (1.0 / curvature).max(half_chord);
+        // The arc's ends sit on the wall's local x axis, so its center lies behind the bulge
+        let center_offset = (radius * radius - half_chord * half_chord).max(0.0).sqrt();
+        Some(WallArc {
+            center: Vec2::new(0.0, -center_offset * mid_angle.sin()),
+            radius,
+            mid_angle,
+            half_angle: (half_chord / radius).min(1.0).asin(),
+        })
+    }
+
+    // Converts a world position into the wall's unrotated frame, centered on the wall
+    fn to_local(&self, position: Vec2) -> Vec2 {
+        let offset = position - Vec2::new(self.center_x, self.center_y);
+        let cos_angle = self.rotation_angle.cos();
+        let sin_angle = self.rotation_angle.sin();
+        Vec2::new(
+            offset.x * cos_angle + offset.y * sin_angle,
+            -offset.x * sin_angle + offset.y * cos_angle
+        )
+    }
+
+    fn to_world(&self, local: Vec2) -> Vec2 {
+        let cos_angle = self.rotation_angle.cos();
+        let sin_angle = self.rotation_angle.sin();
+        Vec2::new(
+            self.center_x + local.x * cos_angle - local.y * sin_angle,
+            self.center_y + local.x * sin_angle + local.y * cos_angle
+        )
+    }
+
+    // Closest point on (or inside) the wall's rectangle or arc band to some world position
+    pub fn closest_point(&self, position: Vec2) -> Vec2 {
+        let local = self.to_local(position);
+        if let Some(arc) = self.arc() {
+            return self.to_world(self.closest_point_on_arc(&arc, local));
+        }
+        let half_size = Vec2::new(self.width / 2.0, self.height / 2.0);
+        self.to_world(local.clamp(-half_size, half_size))
+    }
+
+    // Clamps the angle into the arc's span first, then the distance along that ray into the band.
+    // Past the ends the distance is projected onto the end's ray, so the end caps stay flat
+    fn closest_point_on_arc(&self, arc: &WallArc, local: Vec2) -> Vec2 {
+        let offset = local - arc.center;
+        let angle_from_mid = arc_angle_from_mid(arc, offset);
+        let clamped_angle = angle_from_mid.clamp(-arc.half_angle, arc.half_angle);
+        let half_thickness = self.height / 2.0;
+        let radial = (offset.length() * (angle_from_mid - clamped_angle).cos())
+            .clamp(arc.radius - half_thickness, arc.radius + half_thickness);
+        arc.center + Vec2::from_angle(arc.mid_angle + clamped_angle) * radial
+    }
+
     // Utility function to get corner positions of this wall
     pub fn get_corners(&self) -> [Vec2; 4] {
         let half_width = self.width / 2.0;
@@ -102,4 +425,287 @@ impl Wall {
 
         [top_left, top_right, bottom_right, bottom_left]
     }
-}
\ No newline at end of file
+}
+
+impl WallInteractions for Wall {
+    fn calculate_collision(
+        &self,
+        constants: &PhysicsConstants,
+        obj1: &mut ObjectIn2D,
+        angle: f32,
+        duration: f64,
+        drag_coefficient: f32,
+        cross_sectional_area: f32
+    ) -> Result<(), WallInteractionError> {
+        let mut wall_object = ObjectIn2D::new(
+            IMMOVABLE_WALL_MASS,
+            0.0,
+            0.0,
+            (self.center_x as f64, self.center_y as f64)
+        );
+
+        elastic_collision_2d(
+            constants,
+            obj1,
+            &mut wall_object,
+            angle as f64,
+            duration,
+            drag_coefficient as f64,
+            cross_sectional_area as f64
+        ).map_err(|e| WallInteractionError::CalculationError(e.to_string()))
+    }
+
+    /// Returns the angle of the outward normal of the face closest to the position.
+    /// Positions inside the wall resolve to the face they are least deep behind.
+    /// Curved walls have a normal per point, pointing along the radius through the position
+    fn calculate_wall_face_angle_by_position(
+        &self,
+        position_x: f32,
+        position_y: f32
+    ) -> Result<f32, WallInteractionError> {
+        if !position_x.is_finite() || !position_y.is_finite() {
+            return Err(WallInteractionError::CalculationError(
+                format!("Position ({}, {}) is not finite", position_x, position_y)
+            ));
+        }
+        if self.width <= 0.0 || self.height <= 0.0 {
+            return Err(WallInteractionError::CalculationError(
+                format!("Wall at ({}, {}) has no area", self.center_x, self.center_y)
+            ));
+        }
+
+        let local = self.to_local(Vec2::new(position_x, position_y));
+        if let Some(arc) = self.arc() {
+            let angle = self.rotation_angle + arc_face_normal_angle(&arc, local);
+            return Ok(angle.sin().atan2(angle.cos()));
+        }
+
+        let distance_to_side = self.width / 2.0 - local.x.abs();
+        let distance_to_top = self.height / 2.0 - local.y.abs();
+
+        // Pick the face whose plane the position is closest to, measured from the inside
+        let local_normal_angle = if distance_to_side < distance_to_top {
+            if local.x >= 0.0 { 0.0 } else { std::f32::consts::PI }
+        } else if local.y >= 0.0 {
+            std::f32::consts::FRAC_PI_2
+        } else {
+            -std::f32::consts::FRAC_PI_2
+        };
+
+        let angle = self.rotation_angle + local_normal_angle;
+        Ok(angle.sin().atan2(angle.cos()))
+    }
+}
+
+// Signed angle between the arc's middle and some offset from the arc's center, wrapped to [-PI, PI]
+fn arc_angle_from_mid(arc: &WallArc, offset: Vec2) -> f32 {
+    let angle = offset.to_angle() - arc.mid_angle;
+    angle.sin().atan2(angle.cos())
+}
+
+// Local normal angle of the curved face nearest to a local position.
+// Past the arc's ends the flat end caps face along the arc's tangent
+fn arc_face_normal_angle(arc: &WallArc, local: Vec2) -> f32 {
+    let offset = local - arc.center;
+    let angle_from_mid = arc_angle_from_mid(arc, offset);
+    if angle_from_mid > arc.half_angle {
+        return arc.mid_angle + arc.half_angle + std::f32::consts::FRAC_PI_2;
+    }
+    if angle_from_mid < -arc.half_angle {
+        return arc.mid_angle - arc.half_angle - std::f32::consts::FRAC_PI_2;
+    }
+
+    let radial_angle = arc.mid_angle + angle_from_mid;
+    if offset.length() >= arc.radius {
+        radial_angle
+    } else {
+        radial_angle + std::f32::consts::PI
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lifecycle(open_duration: f32, grow_duration: f32, collapse_duration: f32) -> RipLifecycle {
+        RipLifecycle {
+            open_at: 10.0,
+            open_duration,
+            grow_duration,
+            collapse_duration,
+            max_growth: 2.0,
+        }
+    }
+
+    #[test]
+    fn rip_goes_through_every_phase_in_order() {
+        let lifecycle = lifecycle(2.0, 4.0, 1.0);
+
+        assert_eq!(lifecycle.phase_at(0.0), RipPhase::Dormant);
+        assert_eq!(lifecycle.phase_at(10.0), RipPhase::Opening(0.0));
+        assert_eq!(lifecycle.phase_at(11.0), RipPhase::Opening(0.5));
+        assert_eq!(lifecycle.phase_at(13.0), RipPhase::Growing(0.25));
+        assert_eq!(lifecycle.phase_at(16.5), RipPhase::Collapsing(0.5));
+        assert_eq!(lifecycle.phase_at(17.0), RipPhase::Collapsed);
+        assert_eq!(lifecycle.phase_at(100.0), RipPhase::Collapsed);
+    }
+
+    #[test]
+    fn zero_duration_phases_are_skipped() {
+        let lifecycle = lifecycle(0.0, 4.0, 0.0);
+
+        assert_eq!(lifecycle.phase_at(10.0), RipPhase::Growing(0.0));
+        assert_eq!(lifecycle.phase_at(12.0), RipPhase::Growing(0.5));
+        assert_eq!(lifecycle.phase_at(14.0), RipPhase::Collapsed);
+    }
+
+    #[test]
+    fn all_zero_durations_collapse_at_once() {
+        let lifecycle = lifecycle(0.0, 0.0, 0.0);
+
+        assert_eq!(lifecycle.phase_at(9.9), RipPhase::Dormant);
+        assert_eq!(lifecycle.phase_at(10.0), RipPhase::Collapsed);
+    }
+
+    #[test]
+    fn scale_runs_from_closed_to_max_growth_and_back() {
+        let lifecycle = lifecycle(2.0, 4.0, 1.0);
+
+        assert_eq!(lifecycle.scale(RipPhase::Dormant), 0.0);
+        assert_eq!(lifecycle.scale(RipPhase::Opening(0.5)), 0.5);
+        assert_eq!(lifecycle.scale(RipPhase::Growing(1.0)), 2.0);
+        assert_eq!(lifecycle.scale(RipPhase::Collapsing(0.5)), 1.0);
+        assert_eq!(lifecycle.scale(RipPhase::Collapsed), 0.0);
+    }
+
+    fn assert_close(actual: Vec2, expected: Vec2) {
+        assert!(actual.distance(expected) < 1.0e-3, "{} is not {}", actual, expected);
+    }
+
+    fn assert_angle(actual: f32, expected: f32) {
+        let difference = actual - expected;
+        assert!(difference.sin().atan2(difference.cos()).abs() < 1.0e-4, "{} is not {}", actual, expected);
+    }
+
+    // A 120 wide band curving with a radius of 100 puts the arc's center 80 behind the wall's middle
+    fn curved(wall_shape: WallShape) -> Wall {
+        Wall::new_curved(0.0, 0.0, 120.0, 10.0, 0.0, wall_shape)
+    }
+
+    fn face_normal(wall: &Wall, position: Vec2) -> f32 {
+        wall.calculate_wall_face_angle_by_position(position.x, position.y).unwrap()
+    }
+
+    #[test]
+    fn arc_ends_land_on_the_chord_ends() {
+        for wall_shape in [WallShape::Convex(0.01), WallShape::Concave(0.01), WallShape::Convex(1.0)] {
+            let wall = curved(wall_shape);
+            let arc = wall.arc().unwrap();
+            let ends = [
+                arc.center + Vec2::from_angle(arc.mid_angle - arc.half_angle) * arc.radius,
+                arc.center + Vec2::from_angle(arc.mid_angle + arc.half_angle) * arc.radius,
+            ];
+            let (start, end) = if ends[0].x < ends[1].x { (ends[0], ends[1]) } else { (ends[1], ends[0]) };
+            assert_close(start, Vec2::new(-60.0, 0.0));
+            assert_close(end, Vec2::new(60.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn tightest_arc_is_a_half_circle() {
+        let arc = curved(WallShape::Convex(1.0)).arc().unwrap();
+        assert_eq!(arc.radius, 60.0);
+        assert_close(arc.center, Vec2::ZERO);
+    }
+
+    #[test]
+    fn flat_walls_and_bad_curvatures_have_no_arc() {
+        assert!(Wall::new_rigid(0.0, 0.0, 120.0, 10.0, 0.0).arc().is_none());
+        assert!(curved(WallShape::Convex(0.0)).arc().is_none());
+        assert!(curved(WallShape::Concave(f32::NAN)).arc().is_none());
+    }
+
+    #[test]
+    fn convex_faces_point_away_from_and_toward_the_arc_center() {
+        let wall = curved(WallShape::Convex(0.01));
+
+        // The band's middle peaks at (0, 20), 5 thick to either side
+        assert_close(wall.closest_point(Vec2::new(0.0, 100.0)), Vec2::new(0.0, 25.0));
+        assert_angle(face_normal(&wall, Vec2::new(0.0, 100.0)), std::f32::consts::FRAC_PI_2);
+
+        assert_close(wall.closest_point(Vec2::new(0.0, 0.0)), Vec2::new(0.0, 15.0));
+        assert_angle(face_normal(&wall, Vec2::new(0.0, 0.0)), -std::f32::consts::FRAC_PI_2);
+    }
+
+    #[test]
+    fn concave_inner_normal_points_into_the_hollow() {
+        let wall = curved(WallShape::Concave(0.01));
+
+        // The band dips to (0, -20), the hollow is above it
+        assert_close(wall.closest_point(Vec2::new(0.0, 0.0)), Vec2::new(0.0, -15.0));
+        assert_angle(face_normal(&wall, Vec2::new(0.0, 0.0)), std::f32::consts::FRAC_PI_2);
+
+        assert_close(wall.closest_point(Vec2::new(0.0, -100.0)), Vec2::new(0.0, -25.0));
+        assert_angle(face_normal(&wall, Vec2::new(0.0, -100.0)), -std::f32::consts::FRAC_PI_2);
+    }
+
+    #[test]
+    fn past_either_end_the_flat_end_cap_is_closest() {
+        let wall = curved(WallShape::Convex(0.01));
+        // The ends' rays leave the center (0, -80) at 0.6435 rad either side of straight up
+        let half_angle = 0.6f32.asin();
+
+        assert_close(wall.closest_point(Vec2::new(200.0, 0.0)), Vec2::new(63.0, 4.0));
+        assert_angle(face_normal(&wall, Vec2::new(200.0, 0.0)), -half_angle);
+
+        assert_close(wall.closest_point(Vec2::new(-200.0, 0.0)), Vec2::new(-63.0, 4.0));
+        assert_angle(face_normal(&wall, Vec2::new(-200.0, 0.0)), std::f32::consts::PI + half_angle);
+    }
+
+    #[test]
+    fn rotated_curved_wall_turns_its_normals_with_it() {
+        let mut wall = curved(WallShape::Convex(0.01));
+        wall.rotation_angle = std::f32::consts::FRAC_PI_2;
+
+        assert_close(wall.closest_point(Vec2::new(-100.0, 0.0)), Vec2::new(-25.0, 0.0));
+        assert_angle(face_normal(&wall, Vec2::new(-100.0, 0.0)), std::f32::consts::PI);
+    }
+
+    fn face_angle(wall: &Wall, x: f32, y: f32) -> f32 {
+        wall.calculate_wall_face_angle_by_position(x, y).unwrap()
+    }
+
+    #[test]
+    fn position_inside_a_flat_wall_takes_the_nearest_face() {
+        let wall = Wall::new_rigid(0.0, 0.0, 100.0, 20.0, 0.0);
+
+        assert_angle(face_angle(&wall, 0.0, 5.0), std::f32::consts::FRAC_PI_2);
+        assert_angle(face_angle(&wall, 0.0, -8.0), -std::f32::consts::FRAC_PI_2);
+        assert_angle(face_angle(&wall, 45.0, 0.0), 0.0);
+        assert_angle(face_angle(&wall, -45.0, 3.0), std::f32::consts::PI);
+    }
+
+    #[test]
+    fn position_outside_a_flat_wall_takes_the_face_it_is_beyond() {
+        let wall = Wall::new_rigid(0.0, 0.0, 100.0, 20.0, 0.0);
+
+        assert_angle(face_angle(&wall, 10.0, 30.0), std::f32::consts::FRAC_PI_2);
+        assert_angle(face_angle(&wall, 80.0, 5.0), 0.0);
+    }
+
+    #[test]
+    fn face_normal_turns_with_the_wall() {
+        let wall = Wall::new_rigid(0.0, 0.0, 100.0, 20.0, std::f32::consts::FRAC_PI_2);
+
+        // The wall's top face now looks along -x
+        assert_angle(face_angle(&wall, -5.0, 0.0), std::f32::consts::PI);
+        assert_angle(face_angle(&wall, 0.0, 45.0), std::f32::consts::FRAC_PI_2);
+    }
+
+    #[test]
+    fn face_normal_rejects_bad_input() {
+        let wall = Wall::new_rigid(0.0, 0.0, 100.0, 20.0, 0.0);
+        assert!(wall.calculate_wall_face_angle_by_position(f32::NAN, 0.0).is_err());
+        assert!(Wall::new_rigid(0.0, 0.0, 0.0, 20.0, 0.0).calculate_wall_face_angle_by_position(0.0, 0.0).is_err());
+    }
+}
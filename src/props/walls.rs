@@ -1,140 +1,382 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
-use crate::props::wall_base::Wall;
-use crate::actors::space_time_rip::SpaceTimeRipPlugin;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use rs_physics::models::ObjectIn2D;
+use rs_physics::utils::PhysicsConstants;
+use crate::props::wall_base::{Wall, WallInteractions, WallShape};
+use crate::actors::enemy::{Enemy, ENEMY_CROSS_SECTIONAL_AREA, ENEMY_DRAG_COEFFICIENT, ENEMY_PHYSICS_CONSTANTS, ENEMY_RADIUS};
+use crate::actors::interpolation::WallInterpolation;
+use crate::actors::physics_world::{BodyHandle, PhysicsWorld};
+use crate::actors::player::{Player, PHYSICS_CONSTANTS, PLAYER_CROSS_SECTIONAL_AREA, PLAYER_DRAG_COEFFICIENT, PLAYER_RADIUS};
+use crate::actors::space_time_rip::{SpaceTimeRipMaterial, SpaceTimeRipPlugin};
+use crate::spatial_index::{SpatialIndex, SpatialKind};
+use crate::state::{MainGameState, RipTuning};
 
-// Example plugin that adds the space-time rip walls to your game
+// Rip walls are drawn only through their rip effect, solid walls get a fill and an outline
+pub const RIP_WALL_COLOR: Color = Color::srgba(0.3, 0.3, 0.35, 0.0);
+pub const SOLID_WALL_COLOR: Color = Color::srgb(0.3, 0.32, 0.4);
+const WALL_OUTLINE_COLOR: Color = Color::srgb(0.75, 0.8, 0.95);
+
+// Segments along a curved wall's arc, enough to look smooth at the largest deflector sizes
+const ARC_SEGMENTS: u32 = 32;
+
+// Adds the rendering for the walls and their rips, the layout itself comes from `LevelPlugin`
 pub struct WallsPlugin;
 
 impl Plugin for WallsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(SpaceTimeRipPlugin)
-            .add_systems(PreStartup, spawn_space_time_walls);
+            .add_systems(PostUpdate, draw_wall_outlines.after(TransformSystem::TransformPropagate));
+    }
+}
+
+pub fn wall_color(wall_shape: &WallShape) -> Color {
+    match wall_shape {
+        WallShape::SpaceTimeRip => RIP_WALL_COLOR,
+        _ => SOLID_WALL_COLOR,
+    }
+}
+
+/// What walls and their rips are built from, for the systems spawning whole layouts
+#[derive(SystemParam)]
+pub struct WallAssets<'w> {
+    pub meshes: ResMut<'w, Assets<Mesh>>,
+    pub materials: ResMut<'w, Assets<ColorMaterial>>,
+    pub rip_materials: ResMut<'w, Assets<SpaceTimeRipMaterial>>,
+    pub rip_tuning: Res<'w, RipTuning>,
+}
+
+/// Where a wall comes in its level's list, which also decides the depth of its rip.
+/// Saved levels and runs keep the walls in this order
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct WallOrder(pub u32);
+
+// Helper function to spawn a wall entity, shared by the wall layout and restoring a saved run
+pub fn spawn_wall_entity(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    wall: Wall,
+    order: WallOrder,
+) -> Entity {
+    let mesh = wall_mesh(&wall);
+    let color = wall_color(&wall.wall_shape);
+    let transform = Transform {
+        translation: Vec3::new(wall.center_x, wall.center_y, 0.0),
+        rotation: Quat::from_rotation_z(wall.rotation_angle),
+        ..Default::default()
+    };
+
+    // Only walls on a path move, the others keep the transform they are spawned with
+    let interpolation = wall.path.map(|_| WallInterpolation::new(wall.pose()));
+
+    // Spawn the wall entity
+    let mut entity = commands.spawn((
+        wall,
+        order,
+        Mesh2d(meshes.add(mesh)),
+        MeshMaterial2d(materials.add(color)),
+        transform,
+    ));
+    if let Some(interpolation) = interpolation {
+        entity.insert(interpolation);
     }
+    entity.id()
 }
 
-// System to spawn walls with space-time rips
-pub fn spawn_space_time_walls(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
+// Flat walls are a rectangle, curved walls a band following their arc
+pub fn wall_mesh(wall: &Wall) -> Mesh {
+    let Some(arc) = wall.arc() else {
+        return Rectangle::new(wall.width, wall.height).into();
+    };
+
+    let inner_radius = arc.radius - wall.height / 2.0;
+    let outer_radius = arc.radius + wall.height / 2.0;
+    let mut positions = Vec::with_capacity(2 * (ARC_SEGMENTS as usize + 1));
+    let mut uvs = Vec::with_capacity(positions.capacity());
+
+    for i in 0..=ARC_SEGMENTS {
+        let t = i as f32 / ARC_SEGMENTS as f32;
+        let direction = Vec2::from_angle(arc.mid_angle - arc.half_angle + 2.0 * arc.half_angle * t);
+        let inner = arc.center + direction * inner_radius;
+        let outer = arc.center + direction * outer_radius;
+        positions.push([inner.x, inner.y, 0.0]);
+        positions.push([outer.x, outer.y, 0.0]);
+        uvs.push([t, 1.0]);
+        uvs.push([t, 0.0]);
+    }
+
+    // Two counter-clockwise triangles per segment, between consecutive inner/outer pairs
+    let mut indices = Vec::with_capacity(6 * ARC_SEGMENTS as usize);
+    for i in 0..ARC_SEGMENTS {
+        let inner = 2 * i;
+        let outer = inner + 1;
+        let next_inner = inner + 2;
+        let next_outer = inner + 3;
+        indices.extend_from_slice(&[inner, outer, next_inner, outer, next_outer, next_inner]);
+    }
+
+    let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+// Moves walls along their paths. Their transform is drawn between ticks from the recorded poses,
+// and rip meshes follow their wall's transform on their own
+pub fn move_walls(
+    mut wall_query: Query<(&mut Wall, &mut WallInterpolation)>,
+    game_state: Res<MainGameState>,
 ) {
-    println!("Spawning space-time rip walls...");
-
-    // Create a few example walls
-
-    // Vertical wall with space-time rip
-    spawn_wall(
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-        Vec3::new(-500.0, 0.0, 0.0),       // Position
-        Vec2::new(300.0, 10.0),           // Size (width, height)
-        std::f32::consts::FRAC_PI_2 + std::f32::consts::FRAC_PI_8,                              // Rotation (radians)
-        Color::srgba(0.3, 0.3, 0.35, 0.0),       // Color
-        true                              // Has space-time rip
-    );
+    let time = game_state.run_time as f32;
+    for (mut wall, mut interpolation) in wall_query.iter_mut() {
+        wall.follow_path(time);
+        interpolation.current = wall.pose();
+    }
+}
 
-    // Vertical wall with space-time rip
-    spawn_wall(
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-        Vec3::new(50.0, 350.0, 0.0),       // Position
-        Vec2::new(400.0, 10.0),           // Size (width, height)
-        std::f32::consts::FRAC_PI_2 + std::f32::consts::FRAC_PI_8,                              // Rotation (radians)
-        Color::srgba(0.3, 0.3, 0.35, 0.0),       // Color
-        true                              // Has space-time rip
-    );
+// Bounces the player off solid walls. Space-time rips are left out, they pull the player in instead
+pub fn detect_wall_collisions(
+    player_query: Query<(&Transform, &BodyHandle), With<Player>>,
+    wall_query: Query<&Wall>,
+    mut world: ResMut<PhysicsWorld>,
+    index: Res<SpatialIndex>,
+    time: Res<Time>,
+) {
+    let Ok((player_transform, player_handle)) = player_query.get_single() else {
+        return;
+    };
+    let player_radius = PLAYER_RADIUS * player_transform.scale.x;
+    let Some(player_obj) = world.body_mut(*player_handle) else {
+        return;
+    };
+    let walls = nearby_walls(&index, &wall_query, player_obj, player_radius);
 
-    // Horizontal wall with space-time rip
-    spawn_wall(
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-        Vec3::new(-450.0, 250.0, 0.0),     // Position
-        Vec2::new(300.0, 10.0),           // Size
-        0.0,                              // Rotation
-        Color::srgba(0.3, 0.3, 0.35, 0.0),         // Color
-        true                              // Has space-time rip
+    collide_with_walls(
+        player_obj,
+        player_radius,
+        walls.into_iter(),
+        &PHYSICS_CONSTANTS,
+        time.delta_secs_f64(),
+        PLAYER_DRAG_COEFFICIENT,
+        PLAYER_CROSS_SECTIONAL_AREA,
     );
+}
 
-    // Horizontal wall with space-time rip
-    spawn_wall(
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-        Vec3::new(150.0, 250.0, 0.0),     // Position
-        Vec2::new(300.0, 10.0),           // Size
-        0.0,                              // Rotation
-        Color::srgba(0.3, 0.3, 0.35, 0.0),         // Color
-        true                              // Has space-time rip
-    );
+// Same as the player, enemies bounce off solid walls and fall through rips
+pub fn detect_enemy_wall_collisions(
+    enemy_query: Query<&BodyHandle, With<Enemy>>,
+    wall_query: Query<&Wall>,
+    mut world: ResMut<PhysicsWorld>,
+    index: Res<SpatialIndex>,
+    time: Res<Time>,
+) {
+    for enemy_handle in enemy_query.iter() {
+        let Some(enemy_obj) = world.body_mut(*enemy_handle) else {
+            continue;
+        };
+        let walls = nearby_walls(&index, &wall_query, enemy_obj, ENEMY_RADIUS);
+        collide_with_walls(
+            enemy_obj,
+            ENEMY_RADIUS,
+            walls.into_iter(),
+            &ENEMY_PHYSICS_CONSTANTS,
+            time.delta_secs_f64(),
+            ENEMY_DRAG_COEFFICIENT,
+            ENEMY_CROSS_SECTIONAL_AREA,
+        );
+    }
+}
 
-    // Angled wall with space-time rip
-    spawn_wall(
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-        Vec3::new(200.0, -500.0, 0.0),      // Position
-        Vec2::new(400.0, 10.0),           // Size
-        std::f32::consts::FRAC_PI_6,      // Rotation (30 degrees)
-        Color::srgba(0.3, 0.3, 0.35, 0.0),    // Color
-        true                              // Has space-time rip
-    );
+// The solid walls a body could touch this tick. Being pushed out of one wall moves the body by up to its radius,
+// so the search reaches twice as far to also find the walls it could be pushed into
+fn nearby_walls<'a>(index: &SpatialIndex, wall_query: &'a Query<&Wall>, body: &ObjectIn2D, radius: f32) -> Vec<&'a Wall> {
+    let position = Vec2::new(body.position.x as f32, body.position.y as f32);
+    index.query_radius(position, 2.0 * radius)
+        .into_iter()
+        .filter(|entry| entry.kind == SpatialKind::Wall)
+        .filter_map(|entry| wall_query.get(entry.entity).ok())
+        .collect()
+}
 
-    // Angled wall with space-time rip
-    spawn_wall(
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-        Vec3::new(400.0, -300.0, 0.0),      // Position
-        Vec2::new(200.0, 10.0),           // Size
-        std::f32::consts::FRAC_PI_6 + std::f32::consts::FRAC_PI_8,      // Rotation (30 degrees)
-        Color::srgba(0.3, 0.3, 0.35, 0.0),    // Color
-        true                              // Has space-time rip
-    );
+// Pushes a round body out of every solid wall it overlaps and bounces it off the ones it moves into.
+// Returns whether the body touched any wall
+fn collide_with_walls<'a>(
+    body: &mut ObjectIn2D,
+    radius: f32,
+    walls: impl Iterator<Item = &'a Wall>,
+    constants: &PhysicsConstants,
+    duration: f64,
+    drag_coefficient: f32,
+    cross_sectional_area: f32,
+) -> bool {
+    let mut collided = false;
+
+    for wall in walls.filter(|wall| wall.is_solid()) {
+        let position = Vec2::new(body.position.x as f32, body.position.y as f32);
+        let closest = wall.closest_point(position);
+        let offset = position - closest;
+        if offset.length_squared() >= radius * radius {
+            continue;
+        }
+
+        // Outside the wall the normal points from the contact to the body, inside it comes from the nearest face.
+        // Both follow the surface of curved walls, so deflectors bounce along their local normal
+        let normal = match offset.try_normalize() {
+            Some(normal) => normal,
+            None => match wall.calculate_wall_face_angle_by_position(position.x, position.y) {
+                Ok(angle) => Vec2::from_angle(angle),
+                Err(e) => {
+                    warn!("Wall collision skipped: {:?}", e);
+                    continue;
+                }
+            },
+        };
+        collided = true;
+
+        // Resolve the overlap so the body can't sink into the wall over several ticks
+        let resolved = closest + normal * radius;
+        body.position.x = resolved.x as f64;
+        body.position.y = resolved.y as f64;
+
+        // Bounce in the frame of the wall's surface, so a moving wall hands its velocity on to the body
+        let surface_velocity = wall.velocity_at(closest);
+        let incoming = Vec2::new(body.velocity.x as f32, body.velocity.y as f32) - surface_velocity;
+
+        // Only bounce when moving into the wall, otherwise the body is already leaving it
+        if incoming.dot(normal) >= 0.0 {
+            continue;
+        }
+        body.velocity.x = incoming.x as f64;
+        body.velocity.y = incoming.y as f64;
+
+        if let Err(e) = wall.calculate_collision(
+            constants,
+            body,
+            normal.to_angle(),
+            duration,
+            drag_coefficient,
+            cross_sectional_area,
+        ) {
+            warn!("Wall collision failed: {:?}", e);
+            let outgoing = incoming + surface_velocity;
+            body.velocity.x = outgoing.x as f64;
+            body.velocity.y = outgoing.y as f64;
+            continue;
+        }
+
+        // Only the sliding part of the library's bounce is kept, with the drag over the contact taken off.
+        // The speed away from the wall is the incoming speed mirrored, the wall's surface decides how much of each survives
+        let bounced = Vec2::new(body.velocity.x as f32, body.velocity.y as f32);
+        let rebound_speed = -incoming.dot(normal);
+        let sliding = bounced - normal * bounced.dot(normal);
+        let outgoing = normal * rebound_speed * wall.surface.restitution
+            + sliding * (1.0 - wall.surface.friction).clamp(0.0, 1.0)
+            + surface_velocity;
+        body.velocity.x = outgoing.x as f64;
+        body.velocity.y = outgoing.y as f64;
+    }
 
+    collided
 }
 
-// Helper function to spawn a wall entity
-fn spawn_wall(
-    commands: &mut Commands,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<ColorMaterial>>,
-    position: Vec3,
-    size: Vec2,
-    rotation: f32,
-    color: Color,
-    has_space_time_rip: bool,
+// Outlines the solid walls so they read as barriers, rips are drawn by their own material
+fn draw_wall_outlines(
+    mut gizmos: Gizmos,
+    wall_query: Query<(&Wall, &GlobalTransform)>,
 ) {
-    // Create the wall component
-    let wall = if has_space_time_rip {
-        Wall::new_space_time_rip(
-            position.x,
-            position.y,
-            size.x,
-            size.y,
-            rotation
-        )
-    } else {
-        Wall::new_rigid(
-            position.x,
-            position.y,
-            size.x,
-            size.y,
-            rotation
-        )
+    for (wall, transform) in wall_query.iter() {
+        if !wall.is_solid() {
+            continue;
+        }
+        let outline = wall_outline(wall)
+            .into_iter()
+            .map(|point| transform.transform_point(point.extend(0.0)).truncate());
+        gizmos.linestrip_2d(outline, WALL_OUTLINE_COLOR);
+    }
+}
+
+// Closed loop around the wall's edge in its local frame
+pub(crate) fn wall_outline(wall: &Wall) -> Vec<Vec2> {
+    let Some(arc) = wall.arc() else {
+        let half_width = wall.width / 2.0;
+        let half_height = wall.height / 2.0;
+        return vec![
+            Vec2::new(-half_width, -half_height),
+            Vec2::new(half_width, -half_height),
+            Vec2::new(half_width, half_height),
+            Vec2::new(-half_width, half_height),
+            Vec2::new(-half_width, -half_height),
+        ];
     };
 
-    // Spawn the wall entity
-    commands.spawn((
-        wall,
-        Mesh2d(meshes.add(Rectangle::new(size.x, size.y))),
-        MeshMaterial2d(materials.add(color)),
-        Transform {
-            translation: position,
-            rotation: Quat::from_rotation_z(rotation),
-            ..Default::default()
-        },
-    ));
-}
\ No newline at end of file
+    let inner_radius = arc.radius - wall.height / 2.0;
+    let outer_radius = arc.radius + wall.height / 2.0;
+    let arc_point = |i: u32, radius: f32| {
+        let t = i as f32 / ARC_SEGMENTS as f32;
+        arc.center + Vec2::from_angle(arc.mid_angle - arc.half_angle + 2.0 * arc.half_angle * t) * radius
+    };
+
+    let mut outline: Vec<Vec2> = (0..=ARC_SEGMENTS).map(|i| arc_point(i, outer_radius)).collect();
+    outline.extend((0..=ARC_SEGMENTS).rev().map(|i| arc_point(i, inner_radius)));
+    outline.push(outline[0]);
+    outline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::props::wall_base::WallSurface;
+
+    // A flat 200 by 20 wall across the origin, its top face at y = 10
+    fn floor() -> Wall {
+        let mut wall = Wall::new_rigid(0.0, 0.0, 200.0, 20.0, 0.0);
+        wall.surface = WallSurface { restitution: 0.5, friction: 0.25 };
+        wall
+    }
+
+    fn collide(body: &mut ObjectIn2D, wall: &Wall) -> bool {
+        // No drag, so the library's bounce leaves the sliding speed as it was
+        collide_with_walls(body, 5.0, std::iter::once(wall), &PHYSICS_CONSTANTS, 1.0 / 60.0, 0.0, 1.0)
+    }
+
+    #[test]
+    fn body_bounces_off_a_wall_at_rest() {
+        let mut body = ObjectIn2D::new(1.0, 40.0, -100.0, (0.0, 12.0));
+
+        assert!(collide(&mut body, &floor()));
+        // Pushed back out to touch the top face
+        assert!((body.position.y - 15.0).abs() < 1.0e-4);
+        // Restitution scales the rebound and friction the slide
+        assert!((body.velocity.y - 50.0).abs() < 1.0e-3);
+        assert!((body.velocity.x - 30.0).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn body_leaving_a_wall_keeps_its_velocity() {
+        let mut body = ObjectIn2D::new(1.0, 40.0, 100.0, (0.0, 12.0));
+
+        assert!(collide(&mut body, &floor()));
+        assert_eq!((body.velocity.x, body.velocity.y), (40.0, 100.0));
+    }
+
+    #[test]
+    fn body_clear_of_the_wall_is_left_alone() {
+        let mut body = ObjectIn2D::new(1.0, 0.0, -100.0, (0.0, 30.0));
+
+        assert!(!collide(&mut body, &floor()));
+        assert_eq!((body.position.y, body.velocity.y), (30.0, -100.0));
+    }
+
+    #[test]
+    fn rip_walls_never_bounce() {
+        let rip = Wall::new_space_time_rip(0.0, 0.0, 200.0, 20.0, 0.0);
+        let mut body = ObjectIn2D::new(1.0, 0.0, -100.0, (0.0, 12.0));
+
+        assert!(!collide(&mut body, &rip));
+        assert_eq!(body.velocity.y, -100.0);
+    }
+}
@@ -0,0 +1,63 @@
+use bevy::render::settings::Backends;
+use clap::ValueEnum;
+
+/// Graphics API requested with `--backend`
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum RenderBackend {
+    Auto,
+    Vulkan,
+    Gl,
+    Dx12,
+    Metal,
+}
+
+impl RenderBackend {
+    fn backends(&self) -> Backends {
+        match self {
+            RenderBackend::Auto => platform_default_backends(),
+            RenderBackend::Vulkan => Backends::VULKAN,
+            RenderBackend::Gl => Backends::GL,
+            RenderBackend::Dx12 => Backends::DX12,
+            RenderBackend::Metal => Backends::METAL,
+        }
+    }
+}
+
+/// The native API of each platform, with Vulkan as the choice everywhere else
+fn platform_default_backends() -> Backends {
+    if cfg!(target_os = "windows") {
+        Backends::DX12
+    } else if cfg!(any(target_os = "macos", target_os = "ios")) {
+        Backends::METAL
+    } else {
+        Backends::VULKAN
+    }
+}
+
+// Probes with a throwaway instance, so a missing driver is caught here instead of when bevy creates the device
+fn has_adapter(backends: Backends) -> bool {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends,
+        ..Default::default()
+    });
+    !instance.enumerate_adapters(backends).is_empty()
+}
+
+/// Resolves the requested backend to one that has an adapter on this machine.
+/// Falls back to the platform default and then to anything wgpu can find
+pub fn select_backends(requested: RenderBackend) -> Backends {
+    let requested_backends = requested.backends();
+    if has_adapter(requested_backends) {
+        println!("Using render backend {:?}", requested_backends);
+        return requested_backends;
+    }
+
+    let default_backends = platform_default_backends();
+    if requested_backends != default_backends && has_adapter(default_backends) {
+        println!("Render backend {:?} has no adapter on this machine, falling back to {:?}", requested, default_backends);
+        return default_backends;
+    }
+
+    println!("Render backend {:?} has no adapter on this machine, letting wgpu pick from all backends", requested);
+    Backends::all()
+}
@@ -0,0 +1,367 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use crate::actors::enemy::{Enemy, ENEMY_RADIUS};
+use crate::actors::interpolation::PhysicsInterpolation;
+use crate::actors::physics_world::{BodyHandle, PhysicsWorld};
+use crate::actors::space_time_rip::{scaled_segment, SpaceTimeRipEffect};
+use crate::props::wall_base::Wall;
+
+// Side of a grid cell in world units, about the length of a short wall
+const CELL_SIZE: f32 = 200.0;
+
+/// What an entry in the `SpatialIndex` stands for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpatialKind {
+    Enemy,
+    // Solid walls of any shape
+    Wall,
+    // Space-time rips, covering their whole influence zone rather than just their line
+    Rip,
+}
+
+/// An entity's bounding box in the index
+#[derive(Clone, Copy, Debug)]
+pub struct SpatialEntry {
+    pub entity: Entity,
+    pub kind: SpatialKind,
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+/// Uniform grid over the enemies, walls and rips, rebuilt every fixed tick. Queries only look at the cells they touch,
+/// so they stay cheap however many enemies are alive. Results are candidates by bounding box, the exact test is left to the caller
+#[derive(Resource)]
+pub struct SpatialIndex {
+    cell_size: f32,
+    entries: Vec<SpatialEntry>,
+    cells: HashMap<IVec2, Vec<u32>>,
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        SpatialIndex {
+            cell_size: CELL_SIZE,
+            entries: Vec::new(),
+            cells: HashMap::default(),
+        }
+    }
+}
+
+impl SpatialIndex {
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.cells.clear();
+    }
+
+    pub fn insert(&mut self, entity: Entity, kind: SpatialKind, min: Vec2, max: Vec2) {
+        let index = self.entries.len() as u32;
+        self.entries.push(SpatialEntry { entity, kind, min, max });
+
+        let (min_cell, max_cell) = (self.cell_of(min), self.cell_of(max));
+        for x in min_cell.x..=max_cell.x {
+            for y in min_cell.y..=max_cell.y {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(index);
+            }
+        }
+    }
+
+    // Entries whose bounding box overlaps the box from `min` to `max`
+    pub fn query_aabb(&self, min: Vec2, max: Vec2) -> Vec<SpatialEntry> {
+        let (min_cell, max_cell) = (self.cell_of(min), self.cell_of(max));
+        let mut candidates = Vec::new();
+        for x in min_cell.x..=max_cell.x {
+            for y in min_cell.y..=max_cell.y {
+                self.collect_cell(IVec2::new(x, y), &mut candidates);
+            }
+        }
+
+        self.unique_entries(candidates)
+            .filter(|entry| entry.min.cmple(max).all() && entry.max.cmpge(min).all())
+            .collect()
+    }
+
+    // Entries whose bounding box comes within `radius` of `center`
+    pub fn query_radius(&self, center: Vec2, radius: f32) -> Vec<SpatialEntry> {
+        let mut entries = self.query_aabb(center - Vec2::splat(radius), center + Vec2::splat(radius));
+        entries.retain(|entry| center.clamp(entry.min, entry.max).distance_squared(center) <= radius * radius);
+        entries
+    }
+
+    // Entries whose bounding box the ray hits within `max_distance`, nearest first, with the distance it enters them at.
+    // Walks the grid cell by cell along the ray, so only the cells it passes through are looked at
+    pub fn query_ray(&self, origin: Vec2, direction: Vec2, max_distance: f32) -> Vec<(f32, SpatialEntry)> {
+        let Some(direction) = direction.try_normalize() else {
+            return Vec::new();
+        };
+
+        let step = IVec2::new(axis_step(direction.x), axis_step(direction.y));
+        let mut cell = self.cell_of(origin);
+        let end_cell = self.cell_of(origin + direction * max_distance);
+        // Distance along the ray to the next cell boundary on each axis, and from one boundary to the next
+        let boundary_distance = |cell: i32, step: i32, origin: f32, direction: f32| {
+            if step == 0 {
+                f32::INFINITY
+            } else {
+                ((cell + step.max(0)) as f32 * self.cell_size - origin) / direction
+            }
+        };
+        let mut next_boundary = Vec2::new(
+            boundary_distance(cell.x, step.x, origin.x, direction.x),
+            boundary_distance(cell.y, step.y, origin.y, direction.y),
+        );
+        let boundary_spacing = Vec2::new(
+            if step.x == 0 { f32::INFINITY } else { self.cell_size / direction.x.abs() },
+            if step.y == 0 { f32::INFINITY } else { self.cell_size / direction.y.abs() },
+        );
+
+        let mut candidates = Vec::new();
+        loop {
+            self.collect_cell(cell, &mut candidates);
+            if cell == end_cell || next_boundary.min_element() > max_distance {
+                break;
+            }
+            if next_boundary.x < next_boundary.y {
+                cell.x += step.x;
+                next_boundary.x += boundary_spacing.x;
+            } else {
+                cell.y += step.y;
+                next_boundary.y += boundary_spacing.y;
+            }
+        }
+
+        let mut hits: Vec<(f32, SpatialEntry)> = self.unique_entries(candidates)
+            .filter_map(|entry| {
+                let distance = ray_box_distance(origin, direction, entry.min, entry.max)?;
+                (distance <= max_distance).then_some((distance, entry))
+            })
+            .collect();
+        hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        hits
+    }
+
+    fn cell_of(&self, point: Vec2) -> IVec2 {
+        (point / self.cell_size).floor().as_ivec2()
+    }
+
+    fn collect_cell(&self, cell: IVec2, candidates: &mut Vec<u32>) {
+        if let Some(indices) = self.cells.get(&cell) {
+            candidates.extend_from_slice(indices);
+        }
+    }
+
+    // Large entries sit in several cells, each one is only reported once
+    fn unique_entries(&self, mut candidates: Vec<u32>) -> impl Iterator<Item = SpatialEntry> + '_ {
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates.into_iter().map(|index| self.entries[index as usize])
+    }
+}
+
+fn axis_step(direction: f32) -> i32 {
+    if direction > 0.0 {
+        1
+    } else if direction < 0.0 {
+        -1
+    } else {
+        0
+    }
+}
+
+// Slab test, how far along the ray it enters the box. A ray starting inside the box hits it at 0
+fn ray_box_distance(origin: Vec2, direction: Vec2, min: Vec2, max: Vec2) -> Option<f32> {
+    let mut enter = 0.0_f32;
+    let mut exit = f32::INFINITY;
+    for axis in 0..2 {
+        if direction[axis].abs() < f32::EPSILON {
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let near = (min[axis] - origin[axis]) / direction[axis];
+        let far = (max[axis] - origin[axis]) / direction[axis];
+        enter = enter.max(near.min(far));
+        exit = exit.min(near.max(far));
+        if enter > exit {
+            return None;
+        }
+    }
+    Some(enter)
+}
+
+// Bounds of a wall's rectangle, grown by how far a curved wall bulges past it
+fn wall_bounds(wall: &Wall) -> (Vec2, Vec2) {
+    let corners = wall.get_corners();
+    let min = corners.iter().fold(Vec2::splat(f32::INFINITY), |min, corner| min.min(*corner));
+    let max = corners.iter().fold(Vec2::splat(f32::NEG_INFINITY), |max, corner| max.max(*corner));
+    let bulge = wall.arc()
+        .map(|arc| arc.radius - (arc.radius * arc.radius - wall.width * wall.width / 4.0).max(0.0).sqrt())
+        .unwrap_or(0.0);
+    (min - Vec2::splat(bulge), max + Vec2::splat(bulge))
+}
+
+// Runs once the physics world has stepped. Enemies cover the whole way they moved this tick, so swept tests find them too
+pub fn rebuild_spatial_index(
+    mut index: ResMut<SpatialIndex>,
+    world: Res<PhysicsWorld>,
+    enemy_query: Query<(Entity, &BodyHandle, &PhysicsInterpolation), With<Enemy>>,
+    wall_query: Query<(Entity, &Wall, Option<&SpaceTimeRipEffect>)>,
+) {
+    index.clear();
+
+    for (entity, handle, interpolation) in enemy_query.iter() {
+        let Some(enemy_obj) = world.body(*handle) else {
+            continue;
+        };
+        let position = Vec2::new(enemy_obj.position.x as f32, enemy_obj.position.y as f32);
+        let reach = Vec2::splat(ENEMY_RADIUS);
+        index.insert(
+            entity,
+            SpatialKind::Enemy,
+            position.min(interpolation.previous) - reach,
+            position.max(interpolation.previous) + reach,
+        );
+    }
+
+    for (entity, wall, rip_effect) in wall_query.iter() {
+        match rip_effect {
+            Some(rip_effect) => {
+                // Lifecycles only advance during interactions, so rips cover the most they can grow to.
+                // Whether a rip is open right now is left to the caller
+                let largest_scale = wall.rip_params.lifecycle
+                    .map(|lifecycle| lifecycle.max_growth)
+                    .unwrap_or(1.0);
+                let (start, end) = scaled_segment(wall, largest_scale);
+                let reach = Vec2::splat(rip_effect.influence_distance);
+                index.insert(entity, SpatialKind::Rip, start.min(end) - reach, start.max(end) + reach);
+            }
+            None if wall.is_solid() => {
+                let (min, max) = wall_bounds(wall);
+                index.insert(entity, SpatialKind::Wall, min, max);
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_with(boxes: &[(Vec2, Vec2)]) -> SpatialIndex {
+        let mut index = SpatialIndex::default();
+        for (i, (min, max)) in boxes.iter().enumerate() {
+            index.insert(Entity::from_raw(i as u32), SpatialKind::Enemy, *min, *max);
+        }
+        index
+    }
+
+    fn entities(entries: &[SpatialEntry]) -> Vec<u32> {
+        let mut ids: Vec<u32> = entries.iter().map(|entry| entry.entity.index()).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    #[test]
+    fn aabb_query_finds_overlapping_boxes_only() {
+        let index = index_with(&[
+            (Vec2::new(10.0, 10.0), Vec2::new(20.0, 20.0)),
+            (Vec2::new(500.0, 500.0), Vec2::new(510.0, 510.0)),
+            (Vec2::new(-50.0, -50.0), Vec2::new(-40.0, -40.0)),
+        ]);
+
+        assert_eq!(entities(&index.query_aabb(Vec2::new(0.0, 0.0), Vec2::new(15.0, 15.0))), vec![0]);
+        assert_eq!(entities(&index.query_aabb(Vec2::new(-60.0, -60.0), Vec2::new(15.0, 15.0))), vec![0, 2]);
+        assert!(index.query_aabb(Vec2::new(100.0, 100.0), Vec2::new(150.0, 150.0)).is_empty());
+    }
+
+    #[test]
+    fn box_spanning_many_cells_is_reported_once() {
+        let index = index_with(&[(Vec2::new(-1000.0, -1000.0), Vec2::new(1000.0, 1000.0))]);
+
+        assert_eq!(index.query_aabb(Vec2::new(-900.0, -900.0), Vec2::new(900.0, 900.0)).len(), 1);
+        assert_eq!(index.query_ray(Vec2::new(-900.0, 0.0), Vec2::X, 1800.0).len(), 1);
+    }
+
+    #[test]
+    fn radius_query_skips_boxes_only_its_bounding_square_reaches() {
+        let index = index_with(&[(Vec2::new(10.0, 10.0), Vec2::new(20.0, 20.0))]);
+
+        // The box's nearest corner is about 14.1 away, though the query's bounding square reaches it from 10
+        assert!(index.query_radius(Vec2::ZERO, 12.0).is_empty());
+        assert_eq!(index.query_radius(Vec2::ZERO, 15.0).len(), 1);
+    }
+
+    #[test]
+    fn axis_parallel_ray_hits_boxes_nearest_first() {
+        let index = index_with(&[
+            (Vec2::new(700.0, -5.0), Vec2::new(710.0, 5.0)),
+            (Vec2::new(300.0, -5.0), Vec2::new(310.0, 5.0)),
+            (Vec2::new(300.0, 50.0), Vec2::new(310.0, 60.0)),
+        ]);
+
+        let hits = index.query_ray(Vec2::ZERO, Vec2::new(3.0, 0.0), 1000.0);
+        let order: Vec<u32> = hits.iter().map(|(_, entry)| entry.entity.index()).collect();
+        assert_eq!(order, vec![1, 0]);
+        assert!((hits[0].0 - 300.0).abs() < 1e-3);
+        assert!((hits[1].0 - 700.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn ray_stops_at_its_max_distance() {
+        let index = index_with(&[(Vec2::new(-710.0, -5.0), Vec2::new(-700.0, 5.0))]);
+
+        assert!(index.query_ray(Vec2::ZERO, Vec2::NEG_X, 650.0).is_empty());
+        assert_eq!(index.query_ray(Vec2::ZERO, Vec2::NEG_X, 750.0).len(), 1);
+    }
+
+    #[test]
+    fn diagonal_ray_walks_into_negative_cells() {
+        let index = index_with(&[(Vec2::new(-420.0, -420.0), Vec2::new(-400.0, -400.0))]);
+
+        let hits = index.query_ray(Vec2::new(10.0, 10.0), Vec2::new(-1.0, -1.0), 1000.0);
+        assert_eq!(hits.len(), 1);
+        assert!((hits[0].0 - 410.0 * std::f32::consts::SQRT_2).abs() < 1e-2);
+    }
+
+    #[test]
+    fn zero_length_ray_finds_nothing() {
+        let index = index_with(&[(Vec2::new(-10.0, -10.0), Vec2::new(10.0, 10.0))]);
+
+        assert!(index.query_ray(Vec2::ZERO, Vec2::ZERO, 100.0).is_empty());
+    }
+
+    #[test]
+    fn ray_with_no_distance_only_hits_boxes_it_starts_in() {
+        let index = index_with(&[
+            (Vec2::new(-10.0, -10.0), Vec2::new(10.0, 10.0)),
+            (Vec2::new(20.0, -10.0), Vec2::new(30.0, 10.0)),
+        ]);
+
+        let hits = index.query_ray(Vec2::ZERO, Vec2::X, 0.0);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0, 0.0);
+        assert_eq!(hits[0].1.entity.index(), 0);
+    }
+
+    #[test]
+    fn slab_test_starts_at_zero_inside_the_box() {
+        let distance = ray_box_distance(Vec2::ZERO, Vec2::X, Vec2::splat(-1.0), Vec2::splat(1.0));
+        assert_eq!(distance, Some(0.0));
+    }
+
+    #[test]
+    fn slab_test_axis_parallel_ray_beside_the_box_misses() {
+        let (min, max) = (Vec2::new(5.0, 5.0), Vec2::new(10.0, 10.0));
+
+        assert_eq!(ray_box_distance(Vec2::ZERO, Vec2::X, min, max), None);
+        assert_eq!(ray_box_distance(Vec2::ZERO, Vec2::Y, min, max), None);
+        assert_eq!(ray_box_distance(Vec2::new(0.0, 7.0), Vec2::X, min, max), Some(5.0));
+    }
+
+    #[test]
+    fn slab_test_ignores_boxes_behind_the_ray() {
+        let distance = ray_box_distance(Vec2::ZERO, Vec2::X, Vec2::new(-10.0, -1.0), Vec2::new(-5.0, 1.0));
+        assert_eq!(distance, None);
+    }
+}
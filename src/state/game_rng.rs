@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+/// Single source of randomness for gameplay systems.
+/// Every system that needs a random value draws from this resource, so a run can be reproduced from its seed and inputs
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+    // 32-bit words drawn since the sequence started, a fresh generator skipped ahead by this many is in the same state
+    words_drawn: u64,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        GameRng {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            words_drawn: 0,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn words_drawn(&self) -> u64 {
+        self.words_drawn
+    }
+
+    /// Rewinds the generator to the start of its sequence, used when a new run starts
+    pub fn reseed(&mut self) {
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.words_drawn = 0;
+    }
+
+    /// Puts the generator back where a saved run left it, `words_drawn` words into the sequence of `seed`
+    pub fn restore(&mut self, seed: u64, words_drawn: u64) {
+        self.seed = seed;
+        self.reseed();
+        for _ in 0..words_drawn {
+            self.next_u32();
+        }
+    }
+}
+
+// Every draw goes through here so `words_drawn` stays exact. The generator hands out whole words,
+// a u64 takes two of them and bytes are taken a word at a time
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.words_drawn += 1;
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.words_drawn += 2;
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.words_drawn += dest.len().div_ceil(4) as u64;
+        self.rng.fill_bytes(dest);
+    }
+}
+
+/// Uses the given seed or picks a random one
+pub fn resolve_seed(seed: Option<u64>) -> u64 {
+    seed.unwrap_or_else(rand::random)
+}
+
+// The seed is always logged, so any run can be replayed. Runs at startup, once logging is up
+pub fn log_seed(rng: Res<GameRng>) {
+    info!("Using RNG seed {}", rng.seed());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn restore_continues_the_same_sequence() {
+        let mut original = GameRng::new(42);
+        // Mixed draws of different sizes, enough to cross the generator's block boundaries
+        for i in 0..300 {
+            match i % 4 {
+                0 => { original.next_u32(); }
+                1 => { original.next_u64(); }
+                2 => { original.random_range(0.0..1.0_f64); }
+                _ => { original.fill_bytes(&mut [0u8; 7]); }
+            }
+        }
+
+        let mut restored = GameRng::new(7);
+        restored.restore(original.seed(), original.words_drawn());
+        assert_eq!(restored.words_drawn(), original.words_drawn());
+        for _ in 0..200 {
+            assert_eq!(restored.next_u64(), original.next_u64());
+        }
+    }
+
+    #[test]
+    fn reseed_starts_the_sequence_over() {
+        let mut rng = GameRng::new(9);
+        let first: Vec<u32> = (0..10).map(|_| rng.next_u32()).collect();
+        rng.reseed();
+        let again: Vec<u32> = (0..10).map(|_| rng.next_u32()).collect();
+        assert_eq!(first, again);
+        assert_eq!(rng.words_drawn(), 10);
+    }
+}
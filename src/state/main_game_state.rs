@@ -1,11 +1,34 @@
-use bevy::ecs::system::SystemId;
 use bevy::prelude::*;
+use crate::actors::enemy::Enemy;
+use crate::actors::physics_world::PhysicsWorld;
+use crate::actors::player::{spawn_player, Player, PlayerStart};
+use crate::level::LevelApplied;
+use crate::state::{GameRng, PlayerTuning, TuningApplied};
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(States, Default, PartialEq, Eq, Hash, Clone, Debug)]
 pub enum GameMode {
+    #[default]
     Menu,
     GameRunning,
     GameOver,
+    Editor,
+}
+
+// Only exists while a run is in progress, so leaving the run also clears the pause
+#[derive(SubStates, Default, PartialEq, Eq, Hash, Clone, Debug)]
+#[source(GameMode = GameMode::GameRunning)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+// Ordering of the gameplay systems inside `FixedUpdate`, so every tick runs them in the same order
+#[derive(SystemSet, PartialEq, Eq, Hash, Clone, Debug)]
+pub enum GameplaySet {
+    Input,
+    Physics,
+    Interactions,
 }
 
 #[derive(Resource)]
@@ -15,32 +38,136 @@ pub struct MainGameState {
     pub player_shield: f32,
     pub score: i32,
     pub enemies: Vec<Entity>,
-    pub mode: GameMode,
+    // Seconds of fixed ticks played in this run, unlike `Time` it excludes the menu and pauses
+    pub run_time: f64,
+}
+
+// Set once at startup, it survives restarts unlike `MainGameState`
+#[derive(Resource)]
+pub struct Difficulty(pub f32);
+
+impl Default for MainGameState {
+    fn default() -> Self {
+        MainGameState {
+            player_hp: 100.0,
+            player_energy: 100.0,
+            player_shield: 100.0,
+            score: 0,
+            enemies: vec![],
+            run_time: 0.0,
+        }
+    }
 }
 
 pub fn setup_game_state(mut commands: Commands) {
-    commands.insert_resource(MainGameState {
-        player_hp: 100.0,
-        player_energy: 100.0,
-        player_shield: 100.0,
-        score: 0,
-        enemies: vec![],
-        mode: GameMode::GameRunning,
-    });
+    commands.insert_resource(MainGameState::default());
+}
+
+pub fn advance_run_time(
+    mut state: ResMut<MainGameState>,
+    time: Res<Time>,
+) {
+    state.run_time += time.delta_secs_f64();
 }
 
 pub fn refresh_player_energy(
     mut state: ResMut<MainGameState>,
+    tuning: Res<PlayerTuning>,
 ) {
     if state.player_energy < 100.0 {
-        state.player_energy = (state.player_energy + 0.1).min(100.0);
+        state.player_energy = (state.player_energy + tuning.energy_regen).min(100.0);
     };
 }
 
 pub fn refresh_player_shield(
     mut state: ResMut<MainGameState>,
+    tuning: Res<PlayerTuning>,
 ) {
     if state.player_shield < 100.0 {
-        state.player_shield = (state.player_shield + 0.15).min(100.0);
+        state.player_shield = (state.player_shield + tuning.shield_regen).min(100.0);
     };
-}
\ No newline at end of file
+}
+
+// Run condition for anything that starts a run, the first tick must already see the level's walls and the tuning
+pub fn run_assets_ready(
+    level: Option<Res<LevelApplied>>,
+    tuning: Option<Res<TuningApplied>>,
+) -> bool {
+    level.is_some() && tuning.is_some()
+}
+
+// Leaves the menu / game over screen and starts a run
+pub fn start_game(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_mode: ResMut<NextState<GameMode>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        next_mode.set(GameMode::GameRunning);
+    }
+}
+
+pub fn toggle_pause(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause: ResMut<NextState<PauseState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) || keyboard_input.just_pressed(KeyCode::KeyP) {
+        match pause_state.get() {
+            PauseState::Running => next_pause.set(PauseState::Paused),
+            PauseState::Paused => next_pause.set(PauseState::Running),
+        }
+    }
+}
+
+// Pausing virtual time stops FixedUpdate and freezes `elapsed_secs` for the shader materials.
+// Virtual time does not accumulate while paused, so resuming doesn't produce a catch-up `dt`
+pub fn pause_time(
+    mut time: ResMut<Time<Virtual>>,
+) {
+    time.pause();
+}
+
+pub fn resume_time(
+    mut time: ResMut<Time<Virtual>>,
+) {
+    time.unpause();
+}
+
+pub fn check_game_over(
+    state: Res<MainGameState>,
+    mut next_mode: ResMut<NextState<GameMode>>,
+) {
+    if state.player_hp <= 0.0 {
+        next_mode.set(GameMode::GameOver);
+    }
+}
+
+// Runs when leaving the game over screen so the next run starts from a clean slate
+pub fn restart_run(
+    mut state: ResMut<MainGameState>,
+    mut rng: ResMut<GameRng>,
+) {
+    *state = MainGameState::default();
+    rng.reseed();
+}
+
+// Runs alongside `restart_run`, the next run starts with a fresh player and no enemies
+pub fn respawn_actors(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut world: ResMut<PhysicsWorld>,
+    enemy_query: Query<Entity, With<Enemy>>,
+    player_query: Query<Entity, With<Player>>,
+    player_start: Res<PlayerStart>,
+) {
+    for enemy_entity in enemy_query.iter() {
+        commands.entity(enemy_entity).despawn();
+    }
+
+    for player_entity in player_query.iter() {
+        commands.entity(player_entity).despawn();
+    }
+
+    spawn_player(&mut commands, &mut meshes, &mut materials, &mut world, player_start.0);
+}
@@ -1,3 +1,11 @@
 mod main_game_state;
+mod run_snapshot;
+mod game_rng;
+mod replay;
+mod tuning;
 
-pub use main_game_state::*;
\ No newline at end of file
+pub use main_game_state::*;
+pub use run_snapshot::*;
+pub use game_rng::*;
+pub use replay::*;
+pub use tuning::*;
\ No newline at end of file
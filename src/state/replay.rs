@@ -0,0 +1,201 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::level::LevelPath;
+use crate::level_generator::{GeneratorCenter, GeneratorSettings};
+use crate::state::{CurrentTuning, Difficulty, GameMode, GameRng, Tuning};
+
+/// Bump this whenever the layout of [ReplayFile] changes
+pub const REPLAY_VERSION: u32 = 2;
+
+/// Player controls for a single `FixedUpdate` tick
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct InputFrame {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub boost: bool,
+}
+
+/// The input `player_input` acts on during the current tick, either sampled live or read from a replay
+#[derive(Resource, Default)]
+pub struct PlayerInputFrame(pub InputFrame);
+
+// Boost is a single press, so it's latched every frame until the next fixed tick consumes it.
+// Otherwise it would be dropped or doubled depending on how many ticks run per frame
+#[derive(Resource, Default)]
+pub struct BoostLatch(bool);
+
+/// Everything besides the input that decides how a run plays out. Playback uses these instead of the command line and tuning file
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReplaySettings {
+    pub seed: u64,
+    pub difficulty: f32,
+    pub level: String,
+    pub generate: Option<GeneratorCenter>,
+    pub tuning: Tuning,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReplayFile {
+    pub version: u32,
+    pub settings: ReplaySettings,
+    pub frames: Vec<InputFrame>,
+}
+
+#[derive(Resource)]
+pub enum ReplayMode {
+    Live,
+    Recording {
+        path: PathBuf,
+        frames: Vec<InputFrame>,
+    },
+    Playback {
+        // Boxed, the recorded tuning makes it far larger than the other modes
+        settings: Box<ReplaySettings>,
+        frames: Vec<InputFrame>,
+        cursor: usize,
+    },
+}
+
+impl ReplayMode {
+    /// The settings a replay was recorded with, which must override the command line and tuning file
+    pub fn settings(&self) -> Option<&ReplaySettings> {
+        match self {
+            ReplayMode::Playback { settings, .. } => Some(settings),
+            _ => None,
+        }
+    }
+
+    // Once a recording has started, anything that changes the simulation would no longer match its settings
+    pub fn is_recording_run(&self) -> bool {
+        matches!(self, ReplayMode::Recording { frames, .. } if !frames.is_empty())
+    }
+}
+
+impl ReplayFile {
+    pub fn write_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| e.to_string())
+    }
+
+    pub fn read_from(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let replay: ReplayFile = ron::from_str(&contents).map_err(|e| e.to_string())?;
+        if replay.version != REPLAY_VERSION {
+            return Err(format!("unsupported replay version {}, expected {}", replay.version, REPLAY_VERSION));
+        }
+        Ok(replay)
+    }
+}
+
+/// Builds the mode from the `--record` / `--replay` paths, the replay file is read up front
+pub fn replay_mode_from_paths(record: Option<PathBuf>, replay: Option<PathBuf>) -> ReplayMode {
+    if let Some(path) = replay {
+        let replay = ReplayFile::read_from(&path)
+            .unwrap_or_else(|e| panic!("Failed to read replay {}: {}", path.display(), e));
+        println!("Playing back {} ticks from {}", replay.frames.len(), path.display());
+        return ReplayMode::Playback {
+            settings: Box::new(replay.settings),
+            frames: replay.frames,
+            cursor: 0,
+        };
+    }
+
+    if let Some(path) = record {
+        println!("Recording input to {}", path.display());
+        return ReplayMode::Recording {
+            path,
+            frames: vec![],
+        };
+    }
+
+    ReplayMode::Live
+}
+
+pub fn latch_boost_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut boost_latch: ResMut<BoostLatch>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        boost_latch.0 = true;
+    }
+}
+
+pub fn sample_player_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut boost_latch: ResMut<BoostLatch>,
+    mut replay_mode: ResMut<ReplayMode>,
+    mut input: ResMut<PlayerInputFrame>,
+) {
+    if let ReplayMode::Playback { frames, cursor, .. } = replay_mode.as_mut() {
+        // Once the recording runs out the player simply stops steering
+        input.0 = frames.get(*cursor).copied().unwrap_or_default();
+        if *cursor == frames.len() {
+            info!("Replay finished");
+        }
+        *cursor += 1;
+        return;
+    }
+
+    input.0 = InputFrame {
+        up: keyboard_input.pressed(KeyCode::KeyW),
+        down: keyboard_input.pressed(KeyCode::KeyS),
+        left: keyboard_input.pressed(KeyCode::KeyA),
+        right: keyboard_input.pressed(KeyCode::KeyD),
+        boost: std::mem::take(&mut boost_latch.0),
+    };
+
+    if let ReplayMode::Recording { frames, .. } = replay_mode.as_mut() {
+        frames.push(input.0);
+    }
+}
+
+// Recording covers a single run, it's written out as soon as that run ends or the app closes.
+// Tuning reloads are refused while recording, so the tuning in effect now is the one the whole run used
+pub fn save_recording(
+    mut replay_mode: ResMut<ReplayMode>,
+    rng: Res<GameRng>,
+    difficulty: Res<Difficulty>,
+    level_path: Res<LevelPath>,
+    generator: Option<Res<GeneratorSettings>>,
+    tuning: CurrentTuning,
+) {
+    let ReplayMode::Recording { path, frames } = replay_mode.as_mut() else {
+        return;
+    };
+
+    let replay = ReplayFile {
+        version: REPLAY_VERSION,
+        settings: ReplaySettings {
+            seed: rng.seed(),
+            difficulty: difficulty.0,
+            level: level_path.0.clone(),
+            generate: generator.map(|settings| settings.center),
+            tuning: tuning.to_tuning(),
+        },
+        frames: std::mem::take(frames),
+    };
+    match replay.write_to(path) {
+        Ok(()) => info!("Saved {} ticks of input to {}", replay.frames.len(), path.display()),
+        Err(e) => error!("Failed to save replay: {}", e),
+    }
+
+    *replay_mode = ReplayMode::Live;
+}
+
+// Playback skips the menu so the recorded ticks line up with the start of the run
+pub fn start_replay(
+    replay_mode: Res<ReplayMode>,
+    mut next_mode: ResMut<NextState<GameMode>>,
+) {
+    if matches!(*replay_mode, ReplayMode::Playback { .. }) {
+        next_mode.set(GameMode::GameRunning);
+    }
+}
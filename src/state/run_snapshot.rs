@@ -0,0 +1,393 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use rs_physics::models::ObjectIn2D;
+use serde::{Deserialize, Serialize};
+use crate::actors::enemy::{spawn_enemy_with_object, Enemy};
+use crate::actors::particles::{CosmologicalSimulation, ParticleId, SimulationSnapshot};
+use crate::actors::physics_world::{BodyHandle, PhysicsWorld};
+use crate::actors::player::{spawn_player_with_object, Player};
+use crate::actors::space_time_rip::{rip_z_position, spawn_space_time_rip, RipDebris, SpaceTimeRipEffect, SpaceTimeRipMesh};
+use crate::props::wall_base::{RipPhase, Wall, WallShape};
+use crate::props::walls::{spawn_wall_entity, WallAssets, WallOrder};
+use crate::state::{GameRng, MainGameState, ReplayMode};
+
+/// Bump this whenever the layout of [RunSnapshot] or of anything saved in it, like [Wall], changes
+pub const SNAPSHOT_VERSION: u32 = 4;
+
+pub const QUICKSAVE_PATH: &str = "saves/quicksave.ron";
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(String),
+    Format(String),
+    UnsupportedVersion(u32),
+    Simulation(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(message) => write!(f, "could not access the file: {}", message),
+            SnapshotError::Format(message) => write!(f, "invalid snapshot: {}", message),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "snapshot version {} is not supported, expected {}", version, SNAPSHOT_VERSION)
+            }
+            SnapshotError::Simulation(message) => write!(f, "particle simulation rejected the snapshot: {}", message),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BodySnapshot {
+    pub position: (f64, f64),
+    pub velocity: (f64, f64),
+    pub mass: f64,
+}
+
+impl BodySnapshot {
+    fn from_object(object: &ObjectIn2D) -> Self {
+        BodySnapshot {
+            position: (object.position.x, object.position.y),
+            velocity: (object.velocity.x, object.velocity.y),
+            mass: object.mass,
+        }
+    }
+
+    fn to_object(&self) -> ObjectIn2D {
+        ObjectIn2D::new(self.mass, self.velocity.0, self.velocity.1, self.position)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GameStateSnapshot {
+    pub player_hp: f32,
+    pub player_energy: f32,
+    pub player_shield: f32,
+    pub score: i32,
+    pub run_time: f64,
+}
+
+// Where the run's random sequence had got to
+#[derive(Serialize, Deserialize)]
+pub struct RngSnapshot {
+    pub seed: u64,
+    pub words_drawn: u64,
+}
+
+/// A rip's current gameplay values, which can differ from what the tuning would give it now
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct RipEffectSnapshot {
+    pub influence_distance: f32,
+    pub pull_strength: f32,
+    pub energy_drain: f32,
+    pub shield_damage: f32,
+    pub phase: RipPhase,
+    pub scale: f32,
+}
+
+impl RipEffectSnapshot {
+    fn from_effect(effect: &SpaceTimeRipEffect) -> Self {
+        RipEffectSnapshot {
+            influence_distance: effect.influence_distance,
+            pull_strength: effect.pull_strength,
+            energy_drain: effect.energy_drain,
+            shield_damage: effect.shield_damage,
+            phase: effect.phase,
+            scale: effect.scale,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WallSnapshot {
+    pub wall: Wall,
+    pub rip: Option<RipEffectSnapshot>,
+}
+
+/// Everything needed to rebuild a run mid-play
+#[derive(Serialize, Deserialize)]
+pub struct RunSnapshot {
+    pub version: u32,
+    pub game_state: GameStateSnapshot,
+    pub rng: RngSnapshot,
+    pub player: BodySnapshot,
+    pub enemies: Vec<BodySnapshot>,
+    pub walls: Vec<WallSnapshot>,
+    pub simulation: SimulationSnapshot,
+}
+
+// Only the version is read first so older files are rejected with a clear error instead of a parse failure
+#[derive(Deserialize)]
+struct SnapshotHeader {
+    version: u32,
+}
+
+impl RunSnapshot {
+    pub fn write_to(&self, path: &Path) -> Result<(), SnapshotError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| SnapshotError::Io(e.to_string()))?;
+        }
+        fs::write(path, self.to_ron()?).map_err(|e| SnapshotError::Io(e.to_string()))
+    }
+
+    pub fn read_from(path: &Path) -> Result<Self, SnapshotError> {
+        let contents = fs::read_to_string(path).map_err(|e| SnapshotError::Io(e.to_string()))?;
+        Self::parse(&contents)
+    }
+
+    fn to_ron(&self) -> Result<String, SnapshotError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| SnapshotError::Format(e.to_string()))
+    }
+
+    fn parse(contents: &str) -> Result<Self, SnapshotError> {
+        let header: SnapshotHeader = ron::from_str(contents)
+            .map_err(|e| SnapshotError::Format(e.to_string()))?;
+        if header.version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(header.version));
+        }
+        ron::from_str(contents).map_err(|e| SnapshotError::Format(e.to_string()))
+    }
+}
+
+// What a quick save reads the run from
+#[derive(SystemParam)]
+pub struct RunCapture<'w, 's> {
+    game_state: Res<'w, MainGameState>,
+    rng: Res<'w, GameRng>,
+    world: Res<'w, PhysicsWorld>,
+    simulation: Res<'w, CosmologicalSimulation>,
+    player_query: Query<'w, 's, &'static BodyHandle, With<Player>>,
+    enemy_query: Query<'w, 's, &'static BodyHandle, With<Enemy>>,
+    wall_query: Query<'w, 's, (&'static Wall, &'static WallOrder, Option<&'static SpaceTimeRipEffect>)>,
+}
+
+pub fn quick_save(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    run: RunCapture,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F5) {
+        return;
+    }
+
+    let Some(player_object) = run.player_query.get_single().ok().and_then(|handle| run.world.body(*handle)) else {
+        warn!("Quick save skipped, no player entity");
+        return;
+    };
+
+    // Saved in their level order, so the restored rips are layered the same way
+    let mut walls: Vec<_> = run.wall_query.iter().collect();
+    walls.sort_by_key(|(_, order, _)| **order);
+
+    let snapshot = RunSnapshot {
+        version: SNAPSHOT_VERSION,
+        game_state: GameStateSnapshot {
+            player_hp: run.game_state.player_hp,
+            player_energy: run.game_state.player_energy,
+            player_shield: run.game_state.player_shield,
+            score: run.game_state.score,
+            run_time: run.game_state.run_time,
+        },
+        rng: RngSnapshot {
+            seed: run.rng.seed(),
+            words_drawn: run.rng.words_drawn(),
+        },
+        player: BodySnapshot::from_object(player_object),
+        enemies: run.enemy_query.iter()
+            .filter_map(|handle| run.world.body(*handle))
+            .map(BodySnapshot::from_object)
+            .collect(),
+        walls: walls.into_iter()
+            .map(|(wall, _, rip_effect)| WallSnapshot {
+                wall: wall.clone(),
+                rip: rip_effect.map(RipEffectSnapshot::from_effect),
+            })
+            .collect(),
+        simulation: run.simulation.snapshot(),
+    };
+
+    match snapshot.write_to(Path::new(QUICKSAVE_PATH)) {
+        Ok(()) => info!("Run saved to {}", QUICKSAVE_PATH),
+        Err(e) => error!("Failed to save run: {}", e),
+    }
+}
+
+// Whatever belongs to the run being replaced
+type RunEntities = Or<(With<Player>, With<Enemy>, With<Wall>, With<SpaceTimeRipMesh>, With<RipDebris>)>;
+
+// Everything a quick load replaces or rebuilds the run from
+#[derive(SystemParam)]
+pub struct RunRestore<'w, 's> {
+    assets: WallAssets<'w>,
+    game_state: ResMut<'w, MainGameState>,
+    rng: ResMut<'w, GameRng>,
+    world: ResMut<'w, PhysicsWorld>,
+    simulation: ResMut<'w, CosmologicalSimulation>,
+    despawn_query: Query<'w, 's, Entity, RunEntities>,
+    particle_query: Query<'w, 's, &'static mut Visibility, With<ParticleId>>,
+}
+
+pub fn quick_load(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    replay_mode: Res<ReplayMode>,
+    mut commands: Commands,
+    mut run: RunRestore,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F9) {
+        return;
+    }
+
+    // Jumping to a saved run would leave a recording or replay out of step with its inputs
+    if replay_mode.settings().is_some() || replay_mode.is_recording_run() {
+        warn!("Quick load is unavailable while a run is being recorded or replayed");
+        return;
+    }
+
+    let snapshot = match RunSnapshot::read_from(Path::new(QUICKSAVE_PATH)) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            error!("Failed to load run: {}", e);
+            return;
+        }
+    };
+
+    // Restore the simulation first, it is the only part that can still reject the snapshot
+    if let Err(e) = run.simulation.restore(snapshot.simulation) {
+        error!("Failed to load run: {}", SnapshotError::Simulation(e));
+        return;
+    }
+    // Particles that drifted out of view were hidden, let update_simulation re-evaluate them
+    for mut visibility in run.particle_query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+
+    // The bodies go right away, so the restored ones can take over their slots
+    for entity in run.despawn_query.iter() {
+        if let Some(handle) = run.world.handle_of(entity) {
+            run.world.remove(handle);
+        }
+        commands.entity(entity).despawn();
+    }
+
+    *run.game_state = MainGameState {
+        player_hp: snapshot.game_state.player_hp,
+        player_energy: snapshot.game_state.player_energy,
+        player_shield: snapshot.game_state.player_shield,
+        score: snapshot.game_state.score,
+        run_time: snapshot.game_state.run_time,
+        ..MainGameState::default()
+    };
+    run.rng.restore(snapshot.rng.seed, snapshot.rng.words_drawn);
+
+    spawn_player_with_object(&mut commands, &mut run.assets.meshes, &mut run.assets.materials, &mut run.world, snapshot.player.to_object());
+
+    for enemy in snapshot.enemies.iter() {
+        let enemy_entity = spawn_enemy_with_object(&mut commands, &mut run.assets.meshes, &mut run.assets.materials, &mut run.world, enemy.to_object());
+        run.game_state.enemies.push(enemy_entity);
+    }
+
+    let wall_count = snapshot.walls.len();
+    for (index, WallSnapshot { wall, rip }) in snapshot.walls.into_iter().enumerate() {
+        let has_rip = matches!(wall.wall_shape, WallShape::SpaceTimeRip);
+        let wall_entity = spawn_wall_entity(&mut commands, &mut run.assets.meshes, &mut run.assets.materials, wall.clone(), WallOrder(index as u32));
+        if has_rip {
+            let z_position = rip_z_position(index, wall_count);
+            spawn_space_time_rip(&mut commands, &mut run.assets, wall_entity, &wall, z_position, rip.as_ref());
+        }
+    }
+
+    info!("Run loaded from {}", QUICKSAVE_PATH);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(x: f64) -> BodySnapshot {
+        BodySnapshot { position: (x, 20.0), velocity: (1.5, -2.0), mass: 3.0 }
+    }
+
+    fn snapshot() -> RunSnapshot {
+        RunSnapshot {
+            version: SNAPSHOT_VERSION,
+            game_state: GameStateSnapshot {
+                player_hp: 80.0,
+                player_energy: 40.0,
+                player_shield: 10.0,
+                score: 1200,
+                run_time: 65.5,
+            },
+            rng: RngSnapshot { seed: 42, words_drawn: 317 },
+            player: body(0.0),
+            enemies: vec![body(100.0), body(-100.0)],
+            walls: vec![WallSnapshot {
+                wall: Wall::new_space_time_rip(10.0, 20.0, 300.0, 10.0, 0.5),
+                rip: Some(RipEffectSnapshot {
+                    influence_distance: 150.0,
+                    pull_strength: 2.0,
+                    energy_drain: 5.0,
+                    shield_damage: 1.0,
+                    phase: RipPhase::Growing(0.25),
+                    scale: 0.6,
+                }),
+            }],
+            simulation: SimulationSnapshot {
+                time: 12.0,
+                positions_x: vec![1.0, 2.0],
+                positions_y: vec![3.0, 4.0],
+                velocities_x: vec![0.1, 0.2],
+                velocities_y: vec![0.3, 0.4],
+                masses: vec![500.0, 1500.0],
+                spins: vec![0.0, 1.0],
+                ages: vec![2.0, 3.0],
+                densities: vec![1.0, 1.0],
+            },
+        }
+    }
+
+    #[test]
+    fn saved_run_reads_back_the_same() {
+        let saved = snapshot();
+        let loaded = RunSnapshot::parse(&saved.to_ron().unwrap()).unwrap();
+
+        assert_eq!(loaded.version, SNAPSHOT_VERSION);
+        assert_eq!(loaded.game_state.score, 1200);
+        assert_eq!(loaded.game_state.run_time, 65.5);
+        assert_eq!((loaded.rng.seed, loaded.rng.words_drawn), (42, 317));
+        assert_eq!(loaded.player.position, (0.0, 20.0));
+        assert_eq!(loaded.enemies.len(), 2);
+        assert_eq!(loaded.enemies[1].velocity, (1.5, -2.0));
+        assert_eq!(loaded.walls[0].wall.rotation_angle, 0.5);
+        let rip = loaded.walls[0].rip.unwrap();
+        assert_eq!(rip.phase, RipPhase::Growing(0.25));
+        assert_eq!(rip.influence_distance, 150.0);
+        assert_eq!(loaded.simulation.masses, vec![500.0, 1500.0]);
+    }
+
+    #[test]
+    fn saved_run_survives_the_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("run_snapshot_test_{}.ron", std::process::id()));
+        snapshot().write_to(&path).unwrap();
+        let loaded = RunSnapshot::read_from(&path);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(loaded.unwrap().game_state.player_hp, 80.0);
+    }
+
+    #[test]
+    fn snapshot_from_an_older_version_is_rejected() {
+        let mut old = snapshot();
+        old.version = SNAPSHOT_VERSION - 1;
+        let result = RunSnapshot::parse(&old.to_ron().unwrap());
+        assert!(matches!(result, Err(SnapshotError::UnsupportedVersion(version)) if version == SNAPSHOT_VERSION - 1));
+    }
+
+    #[test]
+    fn version_mismatch_names_both_versions() {
+        let message = SnapshotError::UnsupportedVersion(1).to_string();
+        assert!(message.contains('1') && message.contains(&SNAPSHOT_VERSION.to_string()));
+    }
+}
@@ -0,0 +1,377 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::state::ReplayMode;
+
+pub const TUNING_PATH: &str = "tuning/gameplay.tuning.ron";
+
+/// Player thrust, regeneration and how the player grows from absorbed mass
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PlayerTuning {
+    pub thrust_magnitude: f64,
+    pub boost_multiplier: f64,
+    pub boost_energy_cost: f32,
+    pub velocity_damping: f64,
+    pub stop_speed: f64,
+    pub energy_regen: f32,
+    pub shield_regen: f32,
+    pub absorbed_mass_gain: f64,
+    pub horizon_growth_exponent: f64,
+    pub accretion_ratio: f32,
+}
+
+impl Default for PlayerTuning {
+    fn default() -> Self {
+        PlayerTuning {
+            thrust_magnitude: 200000.0,
+            boost_multiplier: 20.0,
+            boost_energy_cost: 20.0,
+            velocity_damping: 0.98,
+            stop_speed: 60.0,
+            energy_regen: 0.1,
+            shield_regen: 0.15,
+            absorbed_mass_gain: 2.0,
+            horizon_growth_exponent: 1.0 / 3.0,
+            accretion_ratio: 2.0,
+        }
+    }
+}
+
+/// Chance per fixed tick to spawn an enemy while at most `max_enemies` are alive
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SpawnRate {
+    pub max_enemies: usize,
+    pub chance: f64,
+}
+
+/// Enemy spawning, the player's pull on enemies and contact damage
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct EnemyTuning {
+    pub spawn_delay: f64,
+    pub spawn_rates: Vec<SpawnRate>,
+    pub max_spawn_rate: f64,
+    pub min_spawn_speed: f32,
+    pub max_spawn_speed: f32,
+    pub pull_per_player_mass: f64,
+    pub pull_range_squared: f64,
+    pub min_pull_distance: f64,
+    pub max_pull_force: f64,
+    pub ideal_orbit_distance: f32,
+    pub orbit_width: f32,
+    pub orbit_dampening: f64,
+    pub orbital_strength: f64,
+    pub shield_damage: f32,
+    pub hp_damage: f32,
+    pub despawn_height: f32,
+}
+
+impl Default for EnemyTuning {
+    fn default() -> Self {
+        EnemyTuning {
+            spawn_delay: 5.0,
+            spawn_rates: vec![
+                SpawnRate { max_enemies: 10, chance: 0.01 },
+                SpawnRate { max_enemies: 20, chance: 0.03 },
+                SpawnRate { max_enemies: 30, chance: 0.05 },
+            ],
+            max_spawn_rate: 0.075,
+            min_spawn_speed: 100.0,
+            max_spawn_speed: 200.0,
+            pull_per_player_mass: 15000.0,
+            pull_range_squared: 50000.0,
+            min_pull_distance: 200.0,
+            max_pull_force: 200.0,
+            ideal_orbit_distance: 400.0,
+            orbit_width: 200.0,
+            orbit_dampening: 0.02,
+            orbital_strength: 0.8,
+            shield_damage: 25.0,
+            hp_damage: 10.0,
+            despawn_height: -1000.0,
+        }
+    }
+}
+
+impl EnemyTuning {
+    // The spawn half of the tuning, used whenever the level doesn't bring its own rules
+    pub fn spawn_rules(&self) -> EnemySpawnRules {
+        EnemySpawnRules {
+            spawn_delay: self.spawn_delay,
+            spawn_rates: self.spawn_rates.clone(),
+            max_spawn_rate: self.max_spawn_rate,
+            min_spawn_speed: self.min_spawn_speed,
+            max_spawn_speed: self.max_spawn_speed,
+        }
+    }
+}
+
+/// When and how fast enemies spawn. Inserted as a resource by levels that override the tuning file
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct EnemySpawnRules {
+    pub spawn_delay: f64,
+    pub spawn_rates: Vec<SpawnRate>,
+    pub max_spawn_rate: f64,
+    pub min_spawn_speed: f32,
+    pub max_spawn_speed: f32,
+}
+
+impl Default for EnemySpawnRules {
+    fn default() -> Self {
+        EnemyTuning::default().spawn_rules()
+    }
+}
+
+impl EnemySpawnRules {
+    pub fn spawn_rate(&self, enemy_count: usize) -> f64 {
+        self.spawn_rates.iter()
+            .find(|rate| enemy_count <= rate.max_enemies)
+            .map(|rate| rate.chance)
+            .unwrap_or(self.max_spawn_rate)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.spawn_delay.is_finite() && self.spawn_delay >= 0.0) {
+            return Err(format!("spawn_delay must be non-negative, got {}", self.spawn_delay));
+        }
+        if !(self.min_spawn_speed >= 0.0 && self.min_spawn_speed <= self.max_spawn_speed) {
+            return Err(format!(
+                "spawn speeds must satisfy 0 <= min <= max, got {}..={}",
+                self.min_spawn_speed, self.max_spawn_speed
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.max_spawn_rate) {
+            return Err(format!("max_spawn_rate must be within 0..=1, got {}", self.max_spawn_rate));
+        }
+        for (index, rate) in self.spawn_rates.iter().enumerate() {
+            if !(0.0..=1.0).contains(&rate.chance) {
+                return Err(format!("spawn_rates[{}] chance must be within 0..=1, got {}", index, rate.chance));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Values given to each `SpaceTimeRipEffect`
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct RipTuning {
+    pub influence_distance: f32,
+    pub pull_strength: f32,
+    pub energy_drain: f32,
+    pub shield_damage: f32,
+    pub direct_contact_fraction: f32,
+    pub portal_energy_cost: f32,
+    pub shockwave_radius: f32,
+    pub shockwave_speed: f32,
+    pub debris_count: usize,
+    pub debris_speed: f32,
+    pub debris_lifetime: f32,
+}
+
+impl Default for RipTuning {
+    fn default() -> Self {
+        RipTuning {
+            influence_distance: 150.0,
+            pull_strength: 400000.0,
+            energy_drain: 5.0,
+            shield_damage: 2.0,
+            direct_contact_fraction: 0.4,
+            portal_energy_cost: 15.0,
+            shockwave_radius: 350.0,
+            shockwave_speed: 400.0,
+            debris_count: 24,
+            debris_speed: 250.0,
+            debris_lifetime: 1.5,
+        }
+    }
+}
+
+/// The tuning file, any section or field left out keeps its default
+#[derive(Asset, TypePath, Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct Tuning {
+    pub player: PlayerTuning,
+    pub enemy: EnemyTuning,
+    pub rip: RipTuning,
+}
+
+impl Tuning {
+    // Values the gameplay systems can't cope with, like NaN or a spawn speed range that's upside down
+    pub fn validate(&self) -> Result<(), String> {
+        let player = &self.player;
+        validate_non_negative("player", &[
+            ("thrust_magnitude", player.thrust_magnitude),
+            ("boost_multiplier", player.boost_multiplier),
+            ("boost_energy_cost", player.boost_energy_cost as f64),
+            ("stop_speed", player.stop_speed),
+            ("energy_regen", player.energy_regen as f64),
+            ("shield_regen", player.shield_regen as f64),
+            ("absorbed_mass_gain", player.absorbed_mass_gain),
+            ("horizon_growth_exponent", player.horizon_growth_exponent),
+            ("accretion_ratio", player.accretion_ratio as f64),
+        ])?;
+        if !(0.0..=1.0).contains(&player.velocity_damping) {
+            return Err(format!("player.velocity_damping must be within 0..=1, got {}", player.velocity_damping));
+        }
+
+        let enemy = &self.enemy;
+        enemy.spawn_rules().validate().map_err(|message| format!("enemy: {}", message))?;
+        validate_non_negative("enemy", &[
+            ("pull_per_player_mass", enemy.pull_per_player_mass),
+            ("pull_range_squared", enemy.pull_range_squared),
+            ("min_pull_distance", enemy.min_pull_distance),
+            ("max_pull_force", enemy.max_pull_force),
+            ("ideal_orbit_distance", enemy.ideal_orbit_distance as f64),
+            ("orbit_width", enemy.orbit_width as f64),
+            ("orbit_dampening", enemy.orbit_dampening),
+            ("orbital_strength", enemy.orbital_strength),
+            ("shield_damage", enemy.shield_damage as f64),
+            ("hp_damage", enemy.hp_damage as f64),
+        ])?;
+        if !enemy.despawn_height.is_finite() {
+            return Err("enemy.despawn_height must be finite".into());
+        }
+
+        let rip = &self.rip;
+        validate_non_negative("rip", &[
+            ("influence_distance", rip.influence_distance as f64),
+            ("pull_strength", rip.pull_strength as f64),
+            ("energy_drain", rip.energy_drain as f64),
+            ("shield_damage", rip.shield_damage as f64),
+            ("portal_energy_cost", rip.portal_energy_cost as f64),
+            ("shockwave_radius", rip.shockwave_radius as f64),
+            ("shockwave_speed", rip.shockwave_speed as f64),
+            ("debris_speed", rip.debris_speed as f64),
+            ("debris_lifetime", rip.debris_lifetime as f64),
+        ])?;
+        if !(0.0..=1.0).contains(&rip.direct_contact_fraction) {
+            return Err(format!("rip.direct_contact_fraction must be within 0..=1, got {}", rip.direct_contact_fraction));
+        }
+        Ok(())
+    }
+}
+
+fn validate_non_negative(section: &str, values: &[(&str, f64)]) -> Result<(), String> {
+    for (name, value) in values {
+        if !(value.is_finite() && *value >= 0.0) {
+            return Err(format!("{}.{} must be non-negative, got {}", section, name, value));
+        }
+    }
+    Ok(())
+}
+
+/// The tuning resources currently in effect, read together
+#[derive(SystemParam)]
+pub struct CurrentTuning<'w> {
+    pub player: Res<'w, PlayerTuning>,
+    pub enemy: Res<'w, EnemyTuning>,
+    pub rip: Res<'w, RipTuning>,
+}
+
+impl CurrentTuning<'_> {
+    pub fn to_tuning(&self) -> Tuning {
+        Tuning {
+            player: self.player.clone(),
+            enemy: self.enemy.clone(),
+            rip: self.rip.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TuningLoader;
+
+impl AssetLoader for TuningLoader {
+    type Asset = Tuning;
+    type Settings = ();
+    type Error = String;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await.map_err(|e| e.to_string())?;
+        ron::de::from_bytes(&bytes)
+            .map_err(|e| format!("{}: {}", load_context.path().display(), e))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+#[derive(Resource)]
+struct TuningHandle(Handle<Tuning>);
+
+/// Present once the tuning file, or a replay's recorded tuning, is in effect. Runs don't start before that
+#[derive(Resource)]
+pub struct TuningApplied;
+
+/// Loads the tuning asset and keeps the typed resources in sync with it, including live edits.
+/// The resources start out with the built-in defaults until the file has been loaded
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Tuning>()
+            .init_asset_loader::<TuningLoader>()
+            .init_resource::<PlayerTuning>()
+            .init_resource::<EnemyTuning>()
+            .init_resource::<RipTuning>()
+            .add_systems(PreStartup, load_tuning)
+            .add_systems(PreUpdate, apply_tuning);
+    }
+}
+
+fn load_tuning(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(TuningHandle(asset_server.load(TUNING_PATH)));
+}
+
+fn apply_tuning(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<Tuning>>,
+    tuning_assets: Res<Assets<Tuning>>,
+    handle: Res<TuningHandle>,
+    replay_mode: Res<ReplayMode>,
+) {
+    // Edits picked up by the asset watcher, built with the `dev` feature, arrive as `Modified`
+    let changed = events.read()
+        .any(|event| event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0));
+    if !changed {
+        return;
+    }
+
+    // A replay runs with the tuning it was recorded with, and a recording can only save one set of values
+    if replay_mode.settings().is_some() {
+        return;
+    }
+    if replay_mode.is_recording_run() {
+        warn!("Ignoring changes to {} while a run is being recorded", TUNING_PATH);
+        return;
+    }
+
+    if let Some(tuning) = tuning_assets.get(&handle.0) {
+        // A bad edit keeps the values already in effect, which are the defaults if the very first load is bad
+        if let Err(message) = tuning.validate() {
+            warn!("Ignoring invalid tuning in {}: {}", TUNING_PATH, message);
+            commands.insert_resource(TuningApplied);
+            return;
+        }
+        info!("Applying gameplay tuning from {}", TUNING_PATH);
+        commands.insert_resource(tuning.player.clone());
+        commands.insert_resource(tuning.enemy.clone());
+        commands.insert_resource(tuning.rip.clone());
+        commands.insert_resource(TuningApplied);
+    }
+}